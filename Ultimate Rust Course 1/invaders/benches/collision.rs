@@ -0,0 +1,62 @@
+// Compares hit-testing through the occupancy grid with a linear scan of the army,
+// on a playfield that is packed with invaders.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use invaders::frame::{Pos, Rect};
+use invaders::invaders::{Invader, Invaders};
+use invaders::sprite::{self, Collider};
+use invaders::{NUM_COLS, NUM_ROWS};
+
+fn packed_army() -> Vec<Invader> {
+    let mut army = Vec::new();
+    for x in 0..NUM_COLS {
+        for y in 0..NUM_ROWS - 1 {
+            army.push(Invader::new(Pos::new(x, y), sprite::INVADER));
+        }
+    }
+    army
+}
+
+// what `kill_invader_in` did before the grid
+fn kill_linear(army: &mut Vec<Invader>, hitbox: Rect) -> bool {
+    if let Some(idx) = army.iter().position(|invader| invader.hitbox().intersects(&hitbox)) {
+        army.remove(idx);
+        true
+    } else {
+        false
+    }
+}
+
+// one shot per column, aimed at the bottom row of the army
+fn shots() -> Vec<Rect> {
+    (0..NUM_COLS).map(|x| Rect::new(Pos::new(x, NUM_ROWS - 2), 1, 1)).collect()
+}
+
+pub fn collision_benchmark(c: &mut Criterion) {
+    let shots = shots();
+    c.bench_function("kill invaders, grid", |b| {
+        b.iter_batched(
+            || Invaders::from_army(packed_army()),
+            |mut invaders| {
+                for shot in shots.iter() {
+                    black_box(invaders.kill_invader_in(*shot));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("kill invaders, linear scan", |b| {
+        b.iter_batched(
+            packed_army,
+            |mut army| {
+                for shot in shots.iter() {
+                    black_box(kill_linear(&mut army, *shot));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, collision_benchmark);
+criterion_main!(benches);
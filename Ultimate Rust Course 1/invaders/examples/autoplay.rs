@@ -0,0 +1,23 @@
+// Runs bot games without a terminal and prints how they went, e.g. to catch
+// balance regressions: `cargo run --release --example autoplay -- 20 hard 7`,
+// which plays 20 games on hard with seeds 7 to 26.
+
+use std::env;
+use std::time::Duration;
+
+use invaders::bot;
+use invaders::config::Difficulty;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let games = args.next().map(|arg| arg.parse().expect("number of games")).unwrap_or(10);
+    let difficulties = match args.next() {
+        Some(arg) => vec![arg.parse::<Difficulty>().expect("difficulty")],
+        None => Difficulty::ALL.to_vec(),
+    };
+    let seed = args.next().map(|arg| arg.parse().expect("seed")).unwrap_or(0);
+    for difficulty in difficulties {
+        let report = bot::run(&difficulty.config(), seed, games, Duration::from_secs(600));
+        println!("{:>6}: {}", difficulty, report);
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::time::Duration;
+
+use rusty_time::timer::Timer;
+
+use crate::config::Config;
+use crate::frame::{Pos, Rect};
+use crate::game::{Action, Game, Status, TICK};
+use crate::sprite::Collider;
+
+/// plays the game through the same actions as the keyboard
+pub struct Bot {
+    // time between two decisions, so the bot can't react faster than a person
+    reaction: Timer,
+    // where the army was last seen, and which way it went on its last sideways step
+    last_bounds: Option<Rect>,
+    drift: i32,
+}
+
+impl Bot {
+    pub fn new() -> Self {
+        Self::with_reaction(Duration::from_millis(60))
+    }
+
+    pub fn with_reaction(reaction: Duration) -> Self {
+        Self {
+            reaction: Timer::from_millis(reaction.as_millis() as u64),
+            last_bounds: None,
+            drift: 0,
+        }
+    }
+
+    /// an action once the reaction time has passed, `None` in between
+    pub fn update(&mut self, delta: Duration, game: &Game) -> Option<Action> {
+        self.watch(game);
+        self.reaction.update(delta);
+        if !self.reaction.ready {
+            return None;
+        }
+        self.reaction.reset();
+        self.decide(game)
+    }
+
+    /// keeps track of which way the army is heading
+    fn watch(&mut self, game: &Game) {
+        let bounds = game.invaders.army().iter().map(|invader| invader.hitbox()).reduce(|a, b| a.union(&b));
+        if let (Some(last), Some(now)) = (self.last_bounds, bounds) {
+            let dx = now.center().x as i32 - last.center().x as i32;
+            if dx != 0 && now.height == last.height {
+                self.drift = dx.signum();
+            }
+        }
+        self.last_bounds = bounds;
+    }
+
+    /// goes for the lowest invader, the nearest one if several are equally low,
+    /// and fires once the ship is below where it will be when the shot gets there
+    pub fn decide(&self, game: &Game) -> Option<Action> {
+        let ship = game.player.hitbox();
+        let target = game
+            .invaders
+            .army()
+            .iter()
+            .map(|invader| self.lead(game, ship, invader.hitbox()))
+            .max_by_key(|hitbox| {
+                let ship = ship.center().x;
+                let distance = if ship < hitbox.pos.x {
+                    hitbox.pos.x - ship
+                } else {
+                    ship.saturating_sub(hitbox.right() - 1)
+                };
+                (hitbox.bottom(), usize::MAX - distance)
+            })?;
+        let ship = ship.center().x;
+        if ship < target.pos.x {
+            Some(Action::Right)
+        } else if ship >= target.right() {
+            Some(Action::Left)
+        } else {
+            Some(Action::Fire)
+        }
+    }
+
+    /// where `hitbox` will be once a shot fired now reaches its row
+    fn lead(&self, game: &Game, ship: Rect, hitbox: Rect) -> Rect {
+        let rows = ship.pos.y.saturating_sub(hitbox.bottom()) as u32;
+        let travel = game.config.shot_step() * rows;
+        let invaders = &game.invaders;
+        let steps = if travel < invaders.time_to_step() {
+            0
+        } else {
+            let interval = invaders.step_interval().as_millis().max(1);
+            1 + ((travel - invaders.time_to_step()).as_millis() / interval) as i32
+        };
+        let x = (hitbox.pos.x as i32 + steps * self.drift).max(0) as usize;
+        Rect::new(Pos::new(x, hitbox.pos.y), hitbox.width, hitbox.height)
+    }
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// how a batch of bot games went
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub games: usize,
+    pub wins: usize,
+    /// seeds of the games the bot lost, to replay them with `Game::with_seed`
+    pub lost: Vec<u64>,
+    /// summed game time, including games that ran into the time limit
+    pub duration: Duration,
+}
+
+impl Report {
+    pub fn win_rate(&self) -> f32 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.wins as f32 / self.games as f32
+        }
+    }
+
+    pub fn average_duration(&self) -> Duration {
+        if self.games == 0 {
+            Duration::default()
+        } else {
+            self.duration / self.games as u32
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "games: {}, won: {} ({:.0}%), average duration: {:.1}s",
+            self.games,
+            self.wins,
+            self.win_rate() * 100.0,
+            self.average_duration().as_secs_f32()
+        )?;
+        if !self.lost.is_empty() {
+            let seeds: Vec<String> = self.lost.iter().map(u64::to_string).collect();
+            write!(f, ", lost seeds: {}", seeds.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// plays one game without a terminal, as fast as possible.
+/// a game still running after `limit` of game time counts as lost.
+pub fn play(game: &mut Game, bot: &mut Bot, limit: Duration) -> (Status, Duration) {
+    let mut elapsed = Duration::default();
+    while elapsed < limit {
+        if let Some(action) = bot.update(TICK, game) {
+            game.act(action);
+        }
+        game.update(TICK);
+        elapsed += TICK;
+        match game.status() {
+            Status::Playing => {}
+            status => return (status, elapsed),
+        }
+    }
+    (Status::Lost, elapsed)
+}
+
+/// plays `games` bot games with `config`, seeded `seed`, `seed + 1` and so on
+pub fn run(config: &Config, seed: u64, games: usize, limit: Duration) -> Report {
+    let mut report = Report::default();
+    for i in 0..games {
+        let seed = seed.wrapping_add(i as u64);
+        let mut game = Game::with_seed(config.clone(), seed);
+        let (status, duration) = play(&mut game, &mut Bot::new(), limit);
+        report.games += 1;
+        report.duration += duration;
+        if status == Status::Won {
+            report.wins += 1;
+        } else {
+            report.lost.push(seed);
+        }
+    }
+    report
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::frame::{Drawable, Frame, Pos, Rect};
+use crate::invaders::Invaders;
+use crate::sprite::{self, Collider};
+
+/// cover between the ship and the army, one cell per block
+///
+/// a shot that hits a block takes it out, and so does an invader marching into one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bunkers {
+    cells: Vec<Pos>,
+}
+
+impl Bunkers {
+    pub fn new(cells: Vec<Pos>) -> Self {
+        Self { cells }
+    }
+
+    pub fn cells(&self) -> &[Pos] {
+        &self.cells
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// takes out the lowest block on `path`, which a shot travels up, returns its cell
+    pub fn hit_swept(&mut self, path: Rect) -> Option<Pos> {
+        let idx = self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| path.contains(**cell))
+            .max_by_key(|(_, cell)| cell.y)
+            .map(|(idx, _)| idx)?;
+        Some(self.cells.swap_remove(idx))
+    }
+
+    /// takes out every block an invader stands on
+    pub fn erode(&mut self, invaders: &Invaders) {
+        self.cells.retain(|cell| !invaders.army().iter().any(|invader| invader.hitbox().contains(*cell)));
+    }
+}
+
+impl Drawable for Bunkers {
+    fn draw(&self, frame: &mut Frame) {
+        for cell in self.cells.iter() {
+            sprite::BUNKER.draw(frame, *cell, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::invaders::Invader;
+
+    #[test]
+    fn test_shots_take_out_the_lowest_block() {
+        let mut bunkers = Bunkers::new(vec![Pos::new(3, 10), Pos::new(3, 12), Pos::new(4, 12)]);
+        let path = Rect::new(Pos::new(3, 9), 1, 5);
+        assert_eq!(bunkers.hit_swept(path), Some(Pos::new(3, 12)));
+        assert_eq!(bunkers.hit_swept(path), Some(Pos::new(3, 10)));
+        assert_eq!(bunkers.hit_swept(path), None);
+        assert_eq!(bunkers.cells(), &[Pos::new(4, 12)]);
+    }
+
+    #[test]
+    fn test_invaders_erode_blocks() {
+        let mut bunkers = Bunkers::new(vec![Pos::new(5, 6), Pos::new(9, 9)]);
+        bunkers.erode(&Invaders::from_army(vec![Invader::new(Pos::new(4, 5), sprite::INVADER_LARGE)]));
+        assert_eq!(bunkers.cells(), &[Pos::new(9, 9)]);
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// the first line of an asciicast v2 file
+#[derive(Serialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+    timestamp: u64,
+}
+
+/// a render sink that passes everything on to `inner` and also records it,
+/// timestamped, as an asciinema v2 `.cast` file
+///
+/// one output event is written per `flush`, `render` flushes once per frame.
+pub struct CastWriter<W: Write, F: Write> {
+    inner: W,
+    cast: F,
+    start: Instant,
+    // bytes written since the last flush
+    pending: Vec<u8>,
+}
+
+impl<W: Write, F: Write> CastWriter<W, F> {
+    /// writes the header for a terminal of `width` by `height` cells to `cast`
+    pub fn new(inner: W, mut cast: F, width: usize, height: usize) -> io::Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let header = Header { version: 2, width, height, timestamp };
+        writeln!(cast, "{}", serde_json::to_string(&header)?)?;
+        Ok(Self { inner, cast, start: Instant::now(), pending: Vec::new() })
+    }
+}
+
+impl<W: Write, F: Write> Write for CastWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.pending.extend_from_slice(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if !self.pending.is_empty() {
+            let time = self.start.elapsed().as_secs_f64();
+            let data = String::from_utf8_lossy(&self.pending);
+            writeln!(self.cast, "{}", serde_json::to_string(&(time, "o", data))?)?;
+            self.pending.clear();
+        }
+        self.cast.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_output_is_passed_on_and_recorded() {
+        let (mut screen, mut cast) = (Vec::new(), Vec::new());
+        {
+            let mut writer = CastWriter::new(&mut screen, &mut cast, 40, 20).unwrap();
+            write!(writer, "\x1b[1;1H⬢").unwrap();
+            writer.flush().unwrap();
+            // nothing new, no event
+            writer.flush().unwrap();
+        }
+        assert_eq!(String::from_utf8(screen).unwrap(), "\x1b[1;1H⬢");
+        let text = String::from_utf8(cast).unwrap();
+        let lines: Vec<Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!((lines[0]["width"].as_u64(), lines[0]["height"].as_u64()), (Some(40), Some(20)));
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "\x1b[1;1H⬢");
+    }
+}
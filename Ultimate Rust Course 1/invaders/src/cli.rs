@@ -0,0 +1,175 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use log::LevelFilter;
+
+use crate::config::{Accessibility, Difficulty, Size};
+use crate::level::LEVEL_FILE;
+use crate::render::Theme;
+
+/// space invaders in the terminal
+///
+/// without a subcommand the game is played, taking the same flags as `play`.
+#[derive(Debug, Parser)]
+#[command(name = "invaders", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub play: PlayArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// play a game
+    Play(PlayArgs),
+    /// print the high score table
+    Scores,
+    /// print lifetime statistics and achievements
+    Stats,
+    /// design a formation of invaders and bunkers, play it with `--level`
+    Edit {
+        #[arg(default_value = LEVEL_FILE)]
+        file: PathBuf,
+        #[command(flatten)]
+        display: DisplayArgs,
+    },
+    /// watch a recorded game, the last one played is kept in `invaders.replay`
+    Replay {
+        file: PathBuf,
+        #[command(flatten)]
+        display: DisplayArgs,
+    },
+}
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct PlayArgs {
+    /// seed for the game's randomness, the same seed plays the same game
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// preset to start from, overrides the config file [possible values: easy, normal, hard]
+    #[arg(long)]
+    pub difficulty: Option<Difficulty>,
+    /// size of the invaders, overrides the config file [possible values: small, large]
+    #[arg(long)]
+    pub size: Option<Size>,
+    /// play a formation made with `invaders edit` instead of the classic one
+    #[arg(long, value_name = "FILE")]
+    pub level: Option<PathBuf>,
+    /// change waves as they play with a Rhai script, see `invaders::script::Hook`
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+    #[command(flatten)]
+    pub display: DisplayArgs,
+}
+
+/// how the game is shown, for playing as well as watching
+#[derive(Debug, Clone, Default, Args)]
+pub struct DisplayArgs {
+    /// colours to draw in [default: classic] [possible values: classic, mono, neon, contrast]
+    #[arg(long)]
+    pub theme: Option<Theme>,
+    /// keep quiet
+    #[arg(long)]
+    pub no_sound: bool,
+    /// also record the screen to an asciinema v2 file, for `asciinema play`
+    #[arg(long, value_name = "FILE")]
+    pub cast: Option<PathBuf>,
+    /// how much to write to `invaders.log` [default: info] [possible values: off, error, warn, info, debug, trace]
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<LevelFilter>,
+    #[command(flatten)]
+    pub accessibility: AccessibilityArgs,
+}
+
+/// the `[accessibility]` settings of the config file, a flag turns its setting on
+#[derive(Debug, Clone, Default, Args)]
+#[command(next_help_heading = "Accessibility")]
+pub struct AccessibilityArgs {
+    /// draw in the high-contrast `contrast` theme, unless --theme picks another
+    #[arg(long)]
+    pub high_contrast: bool,
+    /// no screen shake, flicker or blinking
+    #[arg(long)]
+    pub reduced_motion: bool,
+    /// large invaders and a large ship
+    #[arg(long)]
+    pub large_glyphs: bool,
+    /// a tone whenever an invader comes into line above the ship
+    #[arg(long)]
+    pub audio_cues: bool,
+}
+
+impl AccessibilityArgs {
+    /// `accessibility` with the flags given turned on
+    pub fn apply(&self, accessibility: Accessibility) -> Accessibility {
+        Accessibility {
+            high_contrast: accessibility.high_contrast || self.high_contrast,
+            reduced_motion: accessibility.reduced_motion || self.reduced_motion,
+            large_glyphs: accessibility.large_glyphs || self.large_glyphs,
+            audio_cues: accessibility.audio_cues || self.audio_cues,
+        }
+    }
+}
+
+impl Command {
+    /// the display flags, for the commands that take over the terminal
+    pub fn display(&self) -> Option<&DisplayArgs> {
+        match self {
+            Command::Play(args) => Some(&args.display),
+            Command::Edit { display, .. } | Command::Replay { display, .. } => Some(display),
+            Command::Scores | Command::Stats => None,
+        }
+    }
+}
+
+impl Cli {
+    /// what to do, playing with the top-level flags when no subcommand was given
+    pub fn command(self) -> Command {
+        self.command.unwrap_or(Command::Play(self.play))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        Cli::try_parse_from(std::iter::once("invaders").chain(args.iter().copied())).map(Cli::command)
+    }
+
+    #[test]
+    fn test_play_is_the_default() {
+        let Ok(Command::Play(args)) = parse(&["--difficulty", "hard", "--no-sound"]) else {
+            panic!("expected play");
+        };
+        assert_eq!(args.difficulty, Some(Difficulty::Hard));
+        assert!(args.display.no_sound);
+        let Ok(command) = parse(&["edit", "--log-level", "debug"]) else {
+            panic!("expected edit");
+        };
+        assert_eq!(command.display().and_then(|display| display.log_level), Some(LevelFilter::Debug));
+        assert!(matches!(parse(&["play", "--seed", "7", "--size", "large"]), Ok(Command::Play(PlayArgs { seed: Some(7), size: Some(Size::Large), .. }))));
+    }
+
+    #[test]
+    fn test_accessibility_flags_add_to_the_config() {
+        let Ok(Command::Play(args)) = parse(&["--reduced-motion", "--audio-cues"]) else {
+            panic!("expected play");
+        };
+        let from_file = Accessibility { large_glyphs: true, ..Accessibility::default() };
+        let accessibility = args.display.accessibility.apply(from_file);
+        assert!(accessibility.reduced_motion && accessibility.audio_cues && accessibility.large_glyphs);
+        assert!(!accessibility.high_contrast);
+    }
+
+    #[test]
+    fn test_bad_values_are_rejected() {
+        assert!(parse(&["--difficulty", "brutal"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--theme", "pink"]).is_err());
+        assert!(parse(&["--log-level", "loud"]).is_err());
+        assert!(parse(&["replay"]).is_err());
+        assert!(parse(&["--seed", "1", "stats"]).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::music::{self, Note};
+use crate::render::Theme;
+use crate::sprite::{self, Sprite};
+
+/// file looked for in the working directory when no other config is given
+pub const CONFIG_FILE: &str = "invaders.toml";
+
+/// every tunable number of the game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+    /// how many shots the player can have on screen at once
+    pub max_shots: usize,
+    /// time it takes a shot to move up one row
+    pub shot_step_ms: u64,
+    /// time between two army steps at the start of a wave
+    pub march_ms: u64,
+    /// how much faster the army steps each time it descends
+    pub march_speedup_ms: u64,
+    /// the army never steps faster than this
+    pub min_march_ms: u64,
+    /// how big the invaders are drawn
+    #[serde(default)]
+    pub size: Size,
+    /// the notes the army's steps play, one sequence per wave, see `music::Soundtrack`
+    #[serde(default = "music::default_march")]
+    pub march_notes: Vec<Vec<Note>>,
+    #[serde(default)]
+    pub accessibility: Accessibility,
+}
+
+impl Config {
+    pub fn shot_step(&self) -> Duration {
+        Duration::from_millis(self.shot_step_ms)
+    }
+
+    /// reads a config file, see `ConfigFile` for the format
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        ConfigFile::load(path)?.build()
+    }
+
+    /// like `load`, but a missing file just means the defaults
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        ConfigFile::load_or_default(path)?.build()
+    }
+
+    /// the sprite waves are made of, large glyphs always get the large one
+    pub fn invader_sprite(&self) -> Sprite {
+        if self.accessibility.large_glyphs {
+            Size::Large.sprite()
+        } else {
+            self.size.sprite()
+        }
+    }
+
+    pub fn player_sprite(&self) -> Sprite {
+        if self.accessibility.large_glyphs {
+            sprite::PLAYER_LARGE
+        } else {
+            sprite::PLAYER
+        }
+    }
+
+    fn validate(self) -> Result<Self, ConfigError> {
+        if self.max_shots == 0 {
+            return Err(ConfigError::Invalid("max_shots must be above 0".into()));
+        }
+        if self.shot_step_ms == 0 {
+            return Err(ConfigError::Invalid("shot_step_ms must be above 0".into()));
+        }
+        if self.min_march_ms == 0 || self.march_ms < self.min_march_ms {
+            return Err(ConfigError::Invalid("march_ms must be at least min_march_ms, which must be above 0".into()));
+        }
+        if self.march_notes.is_empty() || self.march_notes.iter().any(Vec::is_empty) {
+            return Err(ConfigError::Invalid("march_notes must hold at least one sequence, and no empty ones".into()));
+        }
+        Ok(self)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Difficulty::Normal.config()
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<ConfigFile>()?.build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn config(self) -> Config {
+        match self {
+            Difficulty::Easy => Config {
+                max_shots: 3,
+                shot_step_ms: 40,
+                march_ms: 2500,
+                march_speedup_ms: 150,
+                min_march_ms: 400,
+                size: Size::Small,
+                march_notes: music::default_march(),
+                accessibility: Accessibility::default(),
+            },
+            Difficulty::Normal => Config {
+                max_shots: 2,
+                shot_step_ms: 50,
+                march_ms: 2000,
+                march_speedup_ms: 250,
+                min_march_ms: 250,
+                size: Size::Small,
+                march_notes: music::default_march(),
+                accessibility: Accessibility::default(),
+            },
+            Difficulty::Hard => Config {
+                max_shots: 1,
+                shot_step_ms: 60,
+                march_ms: 1500,
+                march_speedup_ms: 300,
+                min_march_ms: 150,
+                size: Size::Small,
+                march_notes: music::default_march(),
+                accessibility: Accessibility::default(),
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|difficulty| difficulty.name() == s)
+            .ok_or_else(|| format!("unknown difficulty `{}`, expected easy, normal or hard", s))
+    }
+}
+
+/// settings that make the game easier to see and hear, the `[accessibility]` table
+/// of the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Accessibility {
+    /// the `contrast` theme, unless another one is picked with `--theme` or `theme`
+    pub high_contrast: bool,
+    /// no screen shake, no flicker between animation frames, no blinking cursor
+    pub reduced_motion: bool,
+    /// large invaders and a large ship, whatever the size setting
+    pub large_glyphs: bool,
+    /// a tone whenever an invader comes into line above the ship
+    pub audio_cues: bool,
+}
+
+/// which invader sprite waves are made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Size {
+    #[default]
+    Small,
+    Large,
+}
+
+impl Size {
+    pub const ALL: [Size; 2] = [Size::Small, Size::Large];
+
+    pub fn sprite(self) -> Sprite {
+        match self {
+            Size::Small => sprite::INVADER,
+            Size::Large => sprite::INVADER_LARGE,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Size::Small => "small",
+            Size::Large => "large",
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|size| size.name() == s)
+            .ok_or_else(|| format!("unknown size `{}`, expected small or large", s))
+    }
+}
+
+/// the TOML layout: a preset to start from, plus any values to override
+///
+/// ```toml
+/// difficulty = "easy"
+/// max_shots = 5
+/// theme = "neon"
+/// # the army's steps in wave 1, 3, 5.. and 2, 4, 6..
+/// march_notes = [["F#2", "F2", "E2", "D#2"], ["A2", "G2", "F2", "E2"]]
+///
+/// [accessibility]
+/// reduced_motion = true
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub difficulty: Option<Difficulty>,
+    pub max_shots: Option<usize>,
+    pub shot_step_ms: Option<u64>,
+    pub march_ms: Option<u64>,
+    pub march_speedup_ms: Option<u64>,
+    pub min_march_ms: Option<u64>,
+    pub size: Option<Size>,
+    pub march_notes: Option<Vec<Vec<Note>>>,
+    /// colours to draw in, `--theme` wins. not part of `Config`, games look the same in any.
+    pub theme: Option<Theme>,
+    pub accessibility: Accessibility,
+}
+
+impl ConfigFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        fs::read_to_string(path.as_ref())?.parse()
+    }
+
+    /// like `load`, but a missing file is the same as an empty one
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        match Self::load(path) {
+            Err(ConfigError::Io(err)) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            other => other,
+        }
+    }
+
+    /// the finished config, after checking the values make sense together
+    pub fn build(&self) -> Result<Config, ConfigError> {
+        self.config().validate()
+    }
+
+    pub fn config(&self) -> Config {
+        let preset = self.difficulty.unwrap_or(Difficulty::Normal).config();
+        Config {
+            max_shots: self.max_shots.unwrap_or(preset.max_shots),
+            shot_step_ms: self.shot_step_ms.unwrap_or(preset.shot_step_ms),
+            march_ms: self.march_ms.unwrap_or(preset.march_ms),
+            march_speedup_ms: self.march_speedup_ms.unwrap_or(preset.march_speedup_ms),
+            min_march_ms: self.min_march_ms.unwrap_or(preset.min_march_ms),
+            size: self.size.unwrap_or(preset.size),
+            march_notes: self.march_notes.clone().unwrap_or(preset.march_notes),
+            accessibility: self.accessibility,
+        }
+    }
+}
+
+impl FromStr for ConfigFile {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read config: {}", err),
+            ConfigError::Parse(msg) => write!(f, "could not parse config: {}", msg),
+            ConfigError::Invalid(msg) => write!(f, "invalid config: {}", msg),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_file_is_normal() {
+        assert_eq!("".parse::<Config>().unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_overrides_apply_on_top_of_preset() {
+        let config: Config = "difficulty = \"hard\"\nmax_shots = 4\n".parse().unwrap();
+        assert_eq!(config.max_shots, 4);
+        assert_eq!(config.march_ms, Difficulty::Hard.config().march_ms);
+        let file: ConfigFile = "theme = \"neon\"".parse().unwrap();
+        assert_eq!(file.theme, Some(Theme::Neon));
+    }
+
+    #[test]
+    fn test_accessibility_table() {
+        let config: Config = "[accessibility]\nlarge_glyphs = true\n".parse().unwrap();
+        assert!(config.accessibility.large_glyphs && !config.accessibility.audio_cues);
+        assert_eq!(config.invader_sprite(), sprite::INVADER_LARGE);
+        assert_eq!(config.player_sprite(), sprite::PLAYER_LARGE);
+        assert!(matches!("[accessibility]\nbig = true".parse::<Config>(), Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_bad_files_are_rejected() {
+        assert!(matches!("difficulty = \"brutal\"".parse::<Config>(), Err(ConfigError::Parse(_))));
+        assert!(matches!("max_shot = 4".parse::<Config>(), Err(ConfigError::Parse(_))));
+        assert!(matches!("max_shots = 0".parse::<Config>(), Err(ConfigError::Invalid(_))));
+        assert!(matches!("theme = \"sepia\"".parse::<Config>(), Err(ConfigError::Parse(_))));
+        assert!(matches!("shot_step_ms = 0".parse::<Config>(), Err(ConfigError::Invalid(_))));
+        assert!(matches!("march_notes = [[\"H2\"]]".parse::<Config>(), Err(ConfigError::Parse(_))));
+        assert!(matches!("march_notes = [[\"C2\"], []]".parse::<Config>(), Err(ConfigError::Invalid(_))));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::events::{GameEvent, Subscriber};
+use crate::frame::{Drawable, Frame, Pos};
+use crate::game::Game;
+
+/// events the overlay lists, the latest last
+pub const LAST_EVENTS: usize = 4;
+
+// row of the first line, below the score line and a reload banner
+const TOP: usize = 2;
+
+/// the game's insides over the game, toggled while playing: entity counts, timers,
+/// frame time and the last events
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    // taken from the game on `update`, so drawing needs nothing else
+    lines: Vec<String>,
+    events: VecDeque<GameEvent>,
+    // smoothed, a single frame says little
+    frame_time: Duration,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// takes the numbers to show from `game`, `delta` is how long the frame took
+    pub fn update(&mut self, game: &Game, delta: Duration) {
+        self.frame_time = if self.frame_time.is_zero() { delta } else { (self.frame_time * 7 + delta) / 8 };
+        if !self.visible {
+            return;
+        }
+        let shots = game.player.shots();
+        let shot_timers: Vec<String> = shots.iter().map(|shot| format!("{}", shot.time_left().as_millis())).collect();
+        self.lines = vec![
+            format!("frame {:.1}ms", self.frame_time.as_secs_f64() * 1000.0),
+            format!("invaders {} bunkers {}", game.invaders.army().len(), game.bunkers.cells().len()),
+            format!("march {}/{}ms", game.invaders.time_to_step().as_millis(), game.invaders.step_interval().as_millis()),
+            format!("shots {}/{} [{}]ms", shots.len(), game.config.max_shots, shot_timers.join(" ")),
+            format!("wave {}/{} seed {}", game.wave + 1, game.waves, game.seed),
+        ];
+        self.lines.extend(self.events.iter().map(|event| format!("{:?}", event)));
+    }
+}
+
+impl Subscriber for DebugOverlay {
+    fn notify(&mut self, event: &GameEvent) {
+        // every shot moves each few ticks, they'd push out everything else
+        if matches!(event, GameEvent::ShotMoved { .. }) {
+            return;
+        }
+        if self.events.len() == LAST_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(*event);
+    }
+}
+
+impl Drawable for DebugOverlay {
+    fn draw(&self, frame: &mut Frame) {
+        if !self.visible {
+            return;
+        }
+        for (idx, line) in self.lines.iter().enumerate() {
+            frame.text(Pos::new(0, TOP + idx), line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::new_frame;
+
+    #[test]
+    fn test_overlay_lists_the_game() {
+        let mut game = Game::new();
+        let mut overlay = DebugOverlay::new();
+        for event in game.update(Duration::from_millis(16)).iter().chain(&[GameEvent::ArmyMoved, GameEvent::ShotMoved { from: Pos::new(1, 1) }]) {
+            overlay.notify(event);
+        }
+        overlay.update(&game, Duration::from_millis(16));
+        let mut frame = new_frame();
+        overlay.draw(&mut frame);
+        assert!(frame.iter().all(|(_, glyph)| glyph == " "), "hidden until toggled");
+
+        overlay.toggle();
+        overlay.update(&game, Duration::from_millis(16));
+        overlay.draw(&mut frame);
+        let text: Vec<String> = (0..frame.height()).map(|y| frame.row(y).collect()).collect();
+        assert!(text[TOP].starts_with("frame 16.0ms"), "{}", text[TOP]);
+        assert!(text[TOP + 1].starts_with(&format!("invaders {} ", game.invaders.army().len())), "{}", text[TOP + 1]);
+        assert!(text.iter().any(|line| line.starts_with("ArmyMoved")));
+        assert!(!text.iter().any(|line| line.starts_with("ShotMoved")));
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::frame::{Drawable, Frame, Pos};
+use crate::level::{Level, KINDS};
+use crate::NUM_ROWS;
+
+/// how much `Faster` and `Slower` change the march speed
+pub const SPEED_STEP_MS: u64 = 100;
+
+// the cursor shows for this long, then hides for as long, so what is under it stays visible
+const BLINK: Duration = Duration::from_millis(400);
+
+/// what the designer can do in `invaders edit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditAction {
+    /// moves the cursor by a signed offset, it stays on the field
+    Move(i32, i32),
+    /// places an invader of `level::KINDS[n]` at the cursor
+    Invader(usize),
+    Bunker,
+    /// removes whatever is under the cursor
+    Remove,
+    Faster,
+    Slower,
+}
+
+/// a level being edited, with a cursor to place things at
+pub struct Editor {
+    pub level: Level,
+    pub cursor: Pos,
+    // changed since it was loaded or saved
+    dirty: bool,
+    // shown in the status line until the next action
+    message: Option<String>,
+    blink: Duration,
+    // reduced motion, the cursor doesn't blink
+    still: bool,
+}
+
+impl Editor {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            cursor: Level::area().center(),
+            dirty: false,
+            message: None,
+            blink: Duration::default(),
+            still: false,
+        }
+    }
+
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        self.still = on;
+    }
+
+    pub fn dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// returns whether the action did anything
+    pub fn apply(&mut self, action: EditAction, default_march_ms: u64) -> bool {
+        self.message = None;
+        // keep the cursor on while it's in use
+        self.blink = Duration::default();
+        let changed = match action {
+            EditAction::Move(dx, dy) => {
+                let area = Level::area();
+                let moved = self.cursor.offset(dx, dy).filter(|pos| area.contains(*pos));
+                if let Some(pos) = moved {
+                    self.cursor = pos;
+                }
+                return moved.is_some();
+            }
+            EditAction::Invader(kind) => match KINDS.get(kind) {
+                Some(sprite) => self.level.place(self.cursor, *sprite),
+                None => false,
+            },
+            EditAction::Bunker => self.level.add_bunker(self.cursor),
+            EditAction::Remove => self.level.remove_at(self.cursor),
+            EditAction::Faster => {
+                let march_ms = self.level.march_ms.unwrap_or(default_march_ms);
+                self.level.march_ms = Some(march_ms.saturating_sub(SPEED_STEP_MS).max(SPEED_STEP_MS));
+                Some(march_ms) != self.level.march_ms
+            }
+            EditAction::Slower => {
+                let march_ms = self.level.march_ms.unwrap_or(default_march_ms);
+                self.level.march_ms = Some(march_ms + SPEED_STEP_MS);
+                true
+            }
+        };
+        self.dirty |= changed;
+        changed
+    }
+
+    /// writes the level to `path` and reports how that went in the status line
+    pub fn save(&mut self, path: &Path) {
+        let result = self.level.clone().validate().and_then(|level| level.save(path));
+        self.message = Some(match result {
+            Ok(()) => {
+                self.dirty = false;
+                format!("saved to {}", path.display())
+            }
+            Err(err) => err.to_string(),
+        });
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.blink += delta;
+        if self.blink >= BLINK * 2 {
+            self.blink -= BLINK * 2;
+        }
+    }
+}
+
+impl Drawable for Editor {
+    fn draw(&self, frame: &mut Frame) {
+        self.level.draw(frame);
+        if self.still || self.blink < BLINK {
+            frame.set(self.cursor, "▒");
+        }
+        let status = match &self.message {
+            Some(message) => message.clone(),
+            None => {
+                let speed = match self.level.march_ms {
+                    Some(march_ms) => format!("{}ms", march_ms),
+                    None => "config".to_string(),
+                };
+                let dirty = if self.dirty { "*" } else { "" };
+                format!(
+                    "{:>2},{:<2} inv {} bunk {} march {}{}",
+                    self.cursor.x,
+                    self.cursor.y,
+                    self.level.invaders.len(),
+                    self.level.bunkers.len(),
+                    speed,
+                    dirty
+                )
+            }
+        };
+        frame.text(Pos::new(0, 0), &status);
+        frame.text_centered(NUM_ROWS - 1, "1 2 inv b bunk x del +- spd s save q");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cursor_stays_on_the_field() {
+        let mut editor = Editor::new(Level::default());
+        editor.cursor = Pos::new(0, 1);
+        assert!(!editor.apply(EditAction::Move(-1, 0), 2000));
+        assert!(!editor.apply(EditAction::Move(0, -1), 2000));
+        assert!(editor.apply(EditAction::Move(1, 1), 2000));
+        assert_eq!(editor.cursor, Pos::new(1, 2));
+        assert!(!editor.dirty());
+    }
+
+    #[test]
+    fn test_placing_and_speed() {
+        let mut editor = Editor::new(Level::default());
+        assert!(editor.apply(EditAction::Invader(1), 2000));
+        assert!(!editor.apply(EditAction::Bunker, 2000));
+        assert!(editor.apply(EditAction::Remove, 2000));
+        assert!(editor.apply(EditAction::Bunker, 2000));
+        assert!(editor.apply(EditAction::Faster, 2000));
+        assert_eq!(editor.level.march_ms, Some(1900));
+        assert!(editor.dirty());
+    }
+}
@@ -0,0 +1,192 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::events::GameEvent;
+use crate::frame::Pos;
+use crate::game::{Action, Game, Status, TICK};
+use crate::sprite::Collider;
+use crate::{NUM_COLS, NUM_ROWS};
+
+/// what a cell of `Observation::grid` holds
+pub const EMPTY: u8 = 0;
+pub const PLAYER: u8 = 1;
+pub const INVADER: u8 = 2;
+pub const SHOT: u8 = 3;
+
+/// rewards handed out by `Env::step`
+pub const REWARD_KILL: f32 = 1.0;
+pub const REWARD_WAVE: f32 = 10.0;
+pub const REWARD_WIN: f32 = 50.0;
+pub const REWARD_LOSS: f32 = -50.0;
+
+/// the discrete action space, index it with the agent's output
+pub const ACTIONS: [Option<Action>; 4] = [None, Some(Action::Left), Some(Action::Right), Some(Action::Fire)];
+
+/// what an agent gets to see after every step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// `NUM_COLS * NUM_ROWS` cells, row by row, holding `EMPTY`, `PLAYER`, `INVADER` or `SHOT`
+    pub grid: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    /// the cell shots are fired from
+    pub player: Pos,
+    /// top-left corners of all invaders
+    pub invaders: Vec<Pos>,
+    pub shots: Vec<Pos>,
+    pub wave: usize,
+}
+
+impl Observation {
+    fn new(game: &Game) -> Self {
+        let (width, height) = (NUM_COLS, NUM_ROWS);
+        let mut grid = vec![EMPTY; width * height];
+        let mut mark = |pos: Pos, value: u8| {
+            if pos.x < width && pos.y < height {
+                grid[pos.y * width + pos.x] = value;
+            }
+        };
+        for invader in game.invaders.army() {
+            invader.hitbox().cells().for_each(|pos| mark(pos, INVADER));
+        }
+        for shot in game.player.shots() {
+            mark(shot.pos, SHOT);
+        }
+        game.player.hitbox().cells().for_each(|pos| mark(pos, PLAYER));
+        Self {
+            grid,
+            width,
+            height,
+            player: game.player.hitbox().center(),
+            invaders: game.invaders.army().iter().map(|invader| invader.pos).collect(),
+            shots: game.player.shots().iter().map(|shot| shot.pos).collect(),
+            wave: game.wave,
+        }
+    }
+
+    pub fn cell(&self, pos: Pos) -> u8 {
+        self.grid[pos.y * self.width + pos.x]
+    }
+}
+
+/// the game as a reinforcement learning environment, gym style
+///
+/// every step applies one action and advances the game by a fixed `tick`, so
+/// episodes don't depend on wall-clock time. runs without a terminal and is `Send`.
+pub struct Env {
+    game: Game,
+    config: Config,
+    tick: Duration,
+    steps: usize,
+    /// episodes are cut off after this many steps
+    pub max_steps: usize,
+}
+
+impl Env {
+    pub fn new(config: Config) -> Self {
+        Self::with_tick(config, TICK)
+    }
+
+    pub fn with_tick(config: Config, tick: Duration) -> Self {
+        Self {
+            game: Game::with_config(config.clone()),
+            config,
+            tick,
+            steps: 0,
+            max_steps: 50_000,
+        }
+    }
+
+    /// starts a new episode. the same seed always gives the same episode.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.steps = 0;
+        self.game = Game::with_seed(self.config.clone(), seed);
+        Observation::new(&self.game)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.game.seed
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// applies `action`, advances the game by one tick and
+    /// returns what the agent sees now, its reward and whether the episode is over
+    pub fn step(&mut self, action: Option<Action>) -> (Observation, f32, bool) {
+        if let Some(action) = action {
+            self.game.act(action);
+        }
+        let events = self.game.update(self.tick);
+        self.steps += 1;
+
+        let reward = events
+            .iter()
+            .map(|event| match event {
+                GameEvent::InvaderKilled { .. } => REWARD_KILL,
+                GameEvent::WaveCleared { .. } => REWARD_WAVE,
+                GameEvent::GameOver { won: true } => REWARD_WIN,
+                GameEvent::GameOver { won: false } => REWARD_LOSS,
+                _ => 0.0,
+            })
+            .sum();
+        let done = self.game.status() != Status::Playing || self.steps >= self.max_steps;
+        (Observation::new(&self.game), reward, done)
+    }
+}
+
+/// many environments stepped side by side on worker threads
+pub struct VecEnv {
+    pub envs: Vec<Env>,
+}
+
+impl VecEnv {
+    pub fn new(config: &Config, count: usize) -> Self {
+        Self { envs: (0..count).map(|_| Env::new(config.clone())).collect() }
+    }
+
+    /// resets environment `i` with `seed + i`, wrapping past `u64::MAX`
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.envs
+            .iter_mut()
+            .enumerate()
+            .map(|(i, env)| env.reset(seed.wrapping_add(i as u64)))
+            .collect()
+    }
+
+    /// steps every environment with its own action, spread over all cores.
+    /// finished environments are reset with their last seed, so they keep going.
+    pub fn step(&mut self, actions: &[Option<Action>]) -> Vec<(Observation, f32, bool)> {
+        assert_eq!(actions.len(), self.envs.len(), "one action per environment");
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let chunk = self.envs.len().div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .envs
+                .chunks_mut(chunk)
+                .zip(actions.chunks(chunk))
+                .map(|(envs, actions)| {
+                    scope.spawn(move || {
+                        envs.iter_mut()
+                            .zip(actions)
+                            .map(|(env, &action)| {
+                                let (obs, reward, done) = env.step(action);
+                                if done {
+                                    let seed = env.seed();
+                                    env.reset(seed);
+                                }
+                                (obs, reward, done)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("environment panicked"))
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use crate::frame::Pos;
+
+/// something that happened in the game, as reported by `Game::update`
+///
+/// the game only describes what happened, audio, scoring, stats and effects
+/// each decide on their own what to make of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    /// the player fired a shot, which starts at `pos`
+    ShotFired { pos: Pos },
+    /// a shot moved up and away from `from`
+    ShotMoved { from: Pos },
+    /// a shot hit an invader at `pos`
+    InvaderKilled { pos: Pos },
+    ArmyMoved,
+    /// an invader came into line above the ship, by its move or the ship's
+    InvaderAbove,
+    /// a shot took out the bunker cell at `pos`
+    BunkerHit { pos: Pos },
+    /// the last invader of `wave` went down, `time` after the wave started
+    WaveCleared { wave: usize, time: Duration },
+    /// the wave script hit an error and stopped, see `Script::error`
+    ScriptFailed,
+    /// the army reached the ship, centred on `pos`
+    PlayerHit { pos: Pos },
+    /// the game was decided, this is always the last event
+    GameOver { won: bool },
+}
+
+/// anything that reacts to game events
+pub trait Subscriber {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+/// hands every event to every subscriber, in order
+pub fn publish(events: &[GameEvent], subscribers: &mut [&mut dyn Subscriber]) {
+    for event in events {
+        for subscriber in subscribers.iter_mut() {
+            subscriber.notify(event);
+        }
+    }
+}
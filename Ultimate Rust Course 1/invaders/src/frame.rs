@@ -1,20 +1,240 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{NUM_COLS, NUM_ROWS};
 
-pub type Frame = Vec<Vec<&'static str>>;
+/// a cell coordinate on the frame, x is the column and y the row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
 
-/// generates an empty frame
-pub fn new_frame() -> Frame {
-    let mut cols = Vec::with_capacity(NUM_COLS);
-    for _ in 0..NUM_COLS {
-        let mut col = Vec::with_capacity(NUM_ROWS);
-        for _ in 0..NUM_ROWS {
-            col.push(" ");
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// moves by a signed offset, `None` if that would go below zero
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Self> {
+        let x = (self.x as i64).checked_add(dx as i64)?;
+        let y = (self.y as i64).checked_add(dy as i64)?;
+        if x < 0 || y < 0 {
+            return None;
         }
-        cols.push(col);
+        Some(Self::new(x as usize, y as usize))
+    }
+}
+
+/// printable ASCII, so any text can be turned into `&'static str` cells
+const ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// the cell for a char of text, `?` for anything that isn't printable ASCII
+pub fn glyph(c: char) -> &'static str {
+    match ASCII.find(c) {
+        Some(idx) => &ASCII[idx..idx + 1],
+        _ => "?",
+    }
+}
+
+/// an axis-aligned block of cells, `pos` is the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rect {
+    pub pos: Pos,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(pos: Pos, width: usize, height: usize) -> Self {
+        Self { pos, width, height }
+    }
+
+    /// first column right of the rect
+    pub fn right(&self) -> usize {
+        self.pos.x + self.width
+    }
+
+    /// first row below the rect
+    pub fn bottom(&self) -> usize {
+        self.pos.y + self.height
+    }
+
+    pub fn center(&self) -> Pos {
+        Pos::new(self.pos.x + self.width / 2, self.pos.y + self.height / 2)
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x >= self.pos.x && pos.x < self.right() && pos.y >= self.pos.y && pos.y < self.bottom()
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.pos.x < other.right()
+            && other.pos.x < self.right()
+            && self.pos.y < other.bottom()
+            && other.pos.y < self.bottom()
+    }
+
+    /// the smallest rect covering both
+    pub fn union(&self, other: &Rect) -> Rect {
+        let pos = Pos::new(self.pos.x.min(other.pos.x), self.pos.y.min(other.pos.y));
+        Rect::new(
+            pos,
+            self.right().max(other.right()) - pos.x,
+            self.bottom().max(other.bottom()) - pos.y,
+        )
+    }
+
+    /// every cell covered by the rect, row by row
+    pub fn cells(&self) -> impl Iterator<Item = Pos> {
+        let rect = *self;
+        (rect.pos.y..rect.bottom()).flat_map(move |y| (rect.pos.x..rect.right()).map(move |x| Pos::new(x, y)))
     }
-    cols
+}
+
+/// a grid of glyphs, stored column by column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<&'static str>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![" "; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    fn index(&self, pos: Pos) -> Option<usize> {
+        if self.contains(pos) {
+            Some(pos.x * self.height + pos.y)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&'static str> {
+        self.index(pos).map(|idx| self.cells[idx])
+    }
+
+    /// sets a cell, returns false (and draws nothing) if `pos` is off-screen
+    pub fn set(&mut self, pos: Pos, glyph: &'static str) -> bool {
+        match self.index(pos) {
+            Some(idx) => {
+                self.cells[idx] = glyph;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// like `set`, but takes signed coordinates so callers can draw partly off-screen
+    pub fn draw_at(&mut self, x: i32, y: i32, glyph: &'static str) -> bool {
+        match Pos::new(0, 0).offset(x, y) {
+            Some(pos) => self.set(pos, glyph),
+            None => false,
+        }
+    }
+
+    /// writes `text` left to right from `pos`, clipped to the frame
+    pub fn text(&mut self, pos: Pos, text: &str) {
+        for (dx, c) in text.chars().enumerate() {
+            self.set(Pos::new(pos.x + dx, pos.y), glyph(c));
+        }
+    }
+
+    /// writes `text` centered on row `y`
+    pub fn text_centered(&mut self, y: usize, text: &str) {
+        let x = self.width.saturating_sub(text.chars().count()) / 2;
+        self.text(Pos::new(x, y), text);
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = " ");
+    }
+
+    /// all cells with their position, column by column
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &'static str)> + '_ {
+        let height = self.height;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, glyph)| (Pos::new(idx / height, idx % height), *glyph))
+    }
+
+    /// the cells of `self` that differ from `other` at the same position
+    pub fn diff<'a>(&'a self, other: &'a Frame) -> impl Iterator<Item = (Pos, &'static str)> + 'a {
+        self.iter().filter(move |(pos, glyph)| other.get(*pos) != Some(*glyph))
+    }
+
+    /// a copy moved by a signed offset, cells moved off-screen are dropped
+    pub fn shifted(&self, dx: i32, dy: i32) -> Frame {
+        let mut shifted = Frame::new(self.width, self.height);
+        for (pos, glyph) in self.iter() {
+            shifted.draw_at(pos.x as i32 + dx, pos.y as i32 + dy, glyph);
+        }
+        shifted
+    }
+
+    /// one row of glyphs, left to right
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &'static str> + '_ {
+        (0..self.width).filter_map(move |x| self.get(Pos::new(x, y)))
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        new_frame()
+    }
+}
+
+/// generates an empty frame
+pub fn new_frame() -> Frame {
+    Frame::new(NUM_COLS, NUM_ROWS)
 }
 
 pub trait  Drawable {
     fn draw(&self, frame: &mut Frame);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get() {
+        let mut frame = Frame::new(3, 2);
+        assert!(frame.set(Pos::new(2, 1), "x"));
+        assert_eq!(frame.get(Pos::new(2, 1)), Some("x"));
+        assert_eq!(frame.get(Pos::new(0, 0)), Some(" "));
+    }
+
+    #[test]
+    fn test_off_screen_is_clipped() {
+        let mut frame = Frame::new(3, 2);
+        assert!(!frame.set(Pos::new(3, 0), "x"));
+        assert!(!frame.draw_at(-1, 0, "x"));
+        assert_eq!(frame.get(Pos::new(3, 0)), None);
+        assert_eq!(frame, Frame::new(3, 2));
+    }
+
+    #[test]
+    fn test_diff() {
+        let last = Frame::new(3, 2);
+        let mut curr = last.clone();
+        curr.set(Pos::new(1, 1), "x");
+        let changed: Vec<_> = curr.diff(&last).collect();
+        assert_eq!(changed, vec![(Pos::new(1, 1), "x")]);
+    }
+}
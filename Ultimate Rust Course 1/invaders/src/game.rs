@@ -0,0 +1,335 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use log::{debug, trace};
+use serde::{Deserialize, Serialize};
+
+use crate::bunkers::Bunkers;
+use crate::config::{Accessibility, Config};
+use crate::events::GameEvent;
+use crate::frame::{Drawable, Frame, Pos};
+use crate::invaders::{Invader, Invaders};
+use crate::level::Level;
+use crate::patterns::PatternKind;
+use crate::player::Player;
+use crate::rng::Rng;
+use crate::script::{Command, Hook, Script, State};
+use crate::sprite::Collider;
+
+/// game time that passes per simulated frame, the game loop and replays step in these
+pub const TICK: Duration = Duration::from_millis(16);
+
+/// points for every invader shot down
+pub const POINTS_PER_INVADER: u32 = 10;
+
+/// the whole game without a terminal, drive it with `act` and `update`.
+/// serializes to everything needed to pick it up again, see `save`.
+#[derive(Serialize, Deserialize)]
+pub struct Game {
+    pub player: Player,
+    pub invaders: Invaders,
+    #[serde(default)]
+    pub bunkers: Bunkers,
+    /// the formation every wave starts from, the classic one if `None`
+    #[serde(default)]
+    pub level: Option<Level>,
+    // the bunkers of a level reloaded mid-wave, put up when the next wave starts
+    #[serde(default)]
+    next_bunkers: Option<Bunkers>,
+    /// changes every wave as it plays, see `script`
+    #[serde(default)]
+    pub script: Option<Script>,
+    /// the current wave, counting from 0
+    pub wave: usize,
+    /// the game is won once this many waves are cleared
+    pub waves: usize,
+    pub score: u32,
+    pub config: Config,
+    /// what `rng` started from, shown at the end so a run can be repeated
+    #[serde(with = "crate::rng::as_i64")]
+    pub seed: u64,
+    pub rng: Rng,
+    // time spent in the current wave
+    #[serde(default)]
+    wave_time: Duration,
+    // whether `GameEvent::GameOver` went out already
+    #[serde(default)]
+    over: bool,
+    // whether an invader was in line above the ship after the last update
+    #[serde(default)]
+    aimed: bool,
+    // events from `act`, handed out with the next update's
+    #[serde(skip)]
+    pending: Vec<GameEvent>,
+}
+
+/// what the player can do, whether from the keyboard or a bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Left,
+    Right,
+    Fire,
+}
+
+/// a new version of a file the game was started from, edited while it runs
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Reload {
+    Config(Config),
+    Level(Level),
+    Script(Box<Script>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Self::with_seed(config, 0)
+    }
+
+    /// the same config and seed always give the same game
+    pub fn with_seed(config: Config, seed: u64) -> Self {
+        let mut player = Player::with_sprite(config.player_sprite());
+        player.set_config(&config);
+        let mut game = Self {
+            player,
+            invaders: Invaders::new(),
+            bunkers: Bunkers::default(),
+            level: None,
+            next_bunkers: None,
+            script: None,
+            wave: 0,
+            waves: PatternKind::ALL.len(),
+            score: 0,
+            config,
+            seed,
+            rng: Rng::new(seed),
+            wave_time: Duration::default(),
+            over: false,
+            aimed: false,
+            pending: Vec::new(),
+        };
+        game.invaders = game.spawn_wave(0);
+        game
+    }
+
+    /// plays `level` from here on: the current wave starts over in its formation,
+    /// behind its bunkers
+    pub fn set_level(&mut self, level: Level) {
+        self.bunkers = level.bunkers();
+        self.level = Some(level);
+        self.start_wave();
+    }
+
+    /// runs `script` from here on: the current wave starts over and its hook runs
+    pub fn set_script(&mut self, script: Script) {
+        self.script = Some(script);
+        self.start_wave();
+    }
+
+    /// plays on with `reload`: the army, the bunkers and the ship's shots change with
+    /// the next wave, the rest right away. the ship keeps its size, its hitbox is part
+    /// of the game.
+    pub fn reload(&mut self, reload: Reload) {
+        match reload {
+            Reload::Config(config) => {
+                let large_glyphs = self.config.accessibility.large_glyphs;
+                self.config = Config { accessibility: Accessibility { large_glyphs, ..config.accessibility }, ..config };
+                self.invaders.set_reduced_motion(self.config.accessibility.reduced_motion);
+            }
+            Reload::Level(level) => {
+                self.next_bunkers = Some(level.bunkers());
+                self.level = Some(level);
+            }
+            Reload::Script(script) => self.script = Some(*script),
+        }
+    }
+
+    // a fresh army for the current wave, as the script wants it
+    fn start_wave(&mut self) {
+        if let Some(bunkers) = self.next_bunkers.take() {
+            self.bunkers = bunkers;
+        }
+        self.player.set_config(&self.config);
+        self.invaders = self.spawn_wave(self.wave);
+        self.run_hook(Hook::Wave);
+        debug!("wave {} starts with {} invaders", self.wave + 1, self.invaders.army().len());
+    }
+
+    /// the game as scripts see it
+    pub fn state(&self) -> State {
+        State {
+            wave: self.wave,
+            waves: self.waves,
+            score: self.score,
+            invaders: self.invaders.army().len(),
+            player_x: self.player.hitbox().center().x,
+        }
+    }
+
+    // calls the script's hook and does what it asked for, a hook that fails
+    // reports it with `GameEvent::ScriptFailed`
+    fn run_hook(&mut self, hook: Hook) {
+        let mut script = match self.script.take() {
+            Some(script) => script,
+            None => return,
+        };
+        let failed = script.error().is_some();
+        let commands = script.run(hook, self.state());
+        if script.error().is_some() && !failed {
+            self.pending.push(GameEvent::ScriptFailed);
+        }
+        self.script = Some(script);
+        for command in commands {
+            match command {
+                Command::Place(pos, sprite) => {
+                    self.invaders.place(Invader::new(pos, sprite));
+                }
+                Command::Clear => self.invaders.clear(),
+                Command::Pattern(kind) => self.invaders.set_pattern(kind.build()),
+                Command::MarchMs(ms) => self.invaders.set_march_ms(ms),
+            }
+        }
+    }
+
+    /// a fresh army, moving in the wave's pattern.
+    /// it holds still for up to one extra step first, how long is up to `rng`.
+    pub fn spawn_wave(&mut self, wave: usize) -> Invaders {
+        let mut config = self.config.clone();
+        let mut invaders = match &self.level {
+            Some(level) => {
+                config.march_ms = level.march_ms.unwrap_or(config.march_ms);
+                Invaders::from_army(level.army())
+            }
+            None => Invaders::with_sprite(self.config.invader_sprite()),
+        };
+        invaders.set_config(&config);
+        invaders.set_pattern(PatternKind::for_wave(wave).build());
+        invaders.delay(Duration::from_millis(self.rng.below(config.march_ms + 1)));
+        invaders
+    }
+
+    /// returns whether the action did anything: the ship moved or a shot was fired
+    pub fn act(&mut self, action: Action) -> bool {
+        let before = self.player.hitbox();
+        match action {
+            Action::Left => self.player.move_left(),
+            Action::Right => self.player.move_right(),
+            Action::Fire => {
+                let fired = self.player.shoot();
+                if let Some(shot) = self.player.shots().last().filter(|_| fired) {
+                    self.pending.push(GameEvent::ShotFired { pos: shot.pos });
+                }
+                return fired;
+            }
+        }
+        self.player.hitbox() != before
+    }
+
+    /// applies the parts of `accessibility` that only change how the game looks and
+    /// sounds, large glyphs change the hitboxes and stay as the game started
+    pub fn set_accessibility(&mut self, accessibility: Accessibility) {
+        self.config.accessibility = Accessibility { large_glyphs: self.config.accessibility.large_glyphs, ..accessibility };
+        self.invaders.set_reduced_motion(accessibility.reduced_motion);
+    }
+
+    /// whether an invader is somewhere in the ship's firing line
+    pub fn invader_above(&self) -> bool {
+        let x = self.player.hitbox().center().x;
+        self.invaders.army().iter().any(|invader| (invader.hitbox().pos.x..invader.hitbox().right()).contains(&x))
+    }
+
+    /// the move that brings the middle of the ship closer to column `x`, `None` once it's there
+    pub fn steer_towards(&self, x: usize) -> Option<Action> {
+        let center = self.player.hitbox().center().x;
+        match x.cmp(&center) {
+            Ordering::Less => Some(Action::Left),
+            Ordering::Greater => Some(Action::Right),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// advances the game by `delta`, returns what happened since the last update
+    pub fn update(&mut self, delta: Duration) -> Vec<GameEvent> {
+        let mut events = std::mem::take(&mut self.pending);
+        self.wave_time += delta;
+        for from in self.player.update(delta) {
+            events.push(GameEvent::ShotMoved { from });
+        }
+        if self.invaders.update(delta) {
+            events.push(GameEvent::ArmyMoved);
+            self.bunkers.erode(&self.invaders);
+            self.run_hook(Hook::ArmyMoved);
+        }
+        for pos in self.player.detect_blocks(&mut self.bunkers) {
+            events.push(GameEvent::BunkerHit { pos });
+        }
+        for pos in self.player.detect_hits(&mut self.invaders) {
+            self.score += POINTS_PER_INVADER;
+            events.push(GameEvent::InvaderKilled { pos });
+            self.run_hook(Hook::InvaderKilled(pos));
+        }
+        let aimed = self.invader_above();
+        if aimed && !self.aimed {
+            events.push(GameEvent::InvaderAbove);
+        }
+        self.aimed = aimed;
+        if self.invaders.all_killed() && !self.over {
+            events.push(GameEvent::WaveCleared { wave: self.wave, time: self.wave_time });
+            if self.wave + 1 < self.waves {
+                self.wave += 1;
+                self.wave_time = Duration::default();
+                self.start_wave();
+            }
+        }
+        // what scripts had to say during this update
+        events.append(&mut self.pending);
+        for event in events.iter() {
+            trace!("{:?}", event);
+        }
+        let status = self.status();
+        if status != Status::Playing && !self.over {
+            self.over = true;
+            if status == Status::Lost {
+                events.push(GameEvent::PlayerHit { pos: self.player.hitbox().center() });
+            }
+            events.push(GameEvent::GameOver { won: status == Status::Won });
+        }
+        events
+    }
+
+    pub fn status(&self) -> Status {
+        if self.invaders.all_killed() {
+            Status::Won
+        } else if self.invaders.reached_row(self.player.hitbox().pos.y) {
+            Status::Lost
+        } else {
+            Status::Playing
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drawable for Game {
+    fn draw(&self, frame: &mut Frame) {
+        frame.text(Pos::new(0, 0), &format!("SCORE {:<6}WAVE {}", self.score, self.wave + 1));
+        self.bunkers.draw(frame);
+        self.player.draw(frame);
+        self.invaders.draw(frame);
+    }
+}
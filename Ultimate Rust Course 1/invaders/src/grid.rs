@@ -0,0 +1,112 @@
+use crate::frame::{Pos, Rect};
+use crate::{NUM_COLS, NUM_ROWS};
+
+/// maps every cell of the playfield to the index of the entity covering it
+///
+/// lookups are O(1), cells outside the playfield are never occupied.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<usize>>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![None; width * height] }
+    }
+
+    fn index(&self, pos: Pos) -> Option<usize> {
+        if pos.x < self.width && pos.y < self.height {
+            Some(pos.x * self.height + pos.y)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<usize> {
+        self.index(pos).and_then(|idx| self.cells[idx])
+    }
+
+    /// marks every on-screen cell of `rect` as covered by `entity`
+    pub fn insert(&mut self, rect: Rect, entity: usize) {
+        for pos in rect.cells() {
+            if let Some(idx) = self.index(pos) {
+                self.cells[idx] = Some(entity);
+            }
+        }
+    }
+
+    /// frees the cells of `rect`, but only those still owned by `entity`
+    pub fn remove(&mut self, rect: Rect, entity: usize) {
+        for pos in rect.cells() {
+            if let Some(idx) = self.index(pos) {
+                if self.cells[idx] == Some(entity) {
+                    self.cells[idx] = None;
+                }
+            }
+        }
+    }
+
+    /// the first entity found in `rect`
+    pub fn find(&self, rect: Rect) -> Option<usize> {
+        rect.cells().find_map(|pos| self.get(pos))
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = None);
+    }
+}
+
+/// an empty grid the size of the playfield
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new(NUM_COLS, NUM_ROWS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_find() {
+        let mut grid = Grid::new(10, 5);
+        grid.insert(Rect::new(Pos::new(2, 1), 3, 2), 7);
+        assert_eq!(grid.get(Pos::new(2, 1)), Some(7));
+        assert_eq!(grid.get(Pos::new(4, 2)), Some(7));
+        assert_eq!(grid.get(Pos::new(5, 2)), None);
+        assert_eq!(grid.find(Rect::new(Pos::new(4, 0), 1, 2)), Some(7));
+        assert_eq!(grid.find(Rect::new(Pos::new(5, 0), 5, 5)), None);
+    }
+
+    #[test]
+    fn test_edges_are_clipped() {
+        let mut grid = Grid::new(10, 5);
+        // hangs over the right and the bottom edge
+        grid.insert(Rect::new(Pos::new(8, 3), 3, 3), 1);
+        assert_eq!(grid.get(Pos::new(9, 4)), Some(1));
+        assert_eq!(grid.get(Pos::new(10, 4)), None);
+        assert_eq!(grid.find(Rect::new(Pos::new(9, 4), 4, 4)), Some(1));
+        grid.insert(Rect::new(Pos::new(0, 0), 2, 2), 2);
+        assert_eq!(grid.get(Pos::new(0, 0)), Some(2));
+        // wholly off the field
+        grid.insert(Rect::new(Pos::new(12, 7), 2, 2), 3);
+        assert_eq!(grid.find(Rect::new(Pos::new(0, 0), 20, 20)), Some(2));
+        grid.remove(Rect::new(Pos::new(8, 3), 3, 3), 1);
+        assert_eq!(grid.get(Pos::new(9, 4)), None);
+    }
+
+    #[test]
+    fn test_remove_only_frees_its_own_cells() {
+        let mut grid = Grid::new(10, 5);
+        grid.insert(Rect::new(Pos::new(0, 0), 3, 1), 1);
+        // overlaps the right end of entity 1
+        grid.insert(Rect::new(Pos::new(2, 0), 2, 1), 2);
+        grid.remove(Rect::new(Pos::new(0, 0), 3, 1), 1);
+        assert_eq!(grid.get(Pos::new(0, 0)), None);
+        assert_eq!(grid.get(Pos::new(2, 0)), Some(2));
+        grid.clear();
+        assert_eq!(grid.find(Rect::new(Pos::new(0, 0), 10, 5)), None);
+    }
+}
@@ -1,103 +1,292 @@
-use std::{cmp::max, time::Duration, usize};
+use std::{cmp::max, time::Duration};
 
 use rusty_time::timer::Timer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{frame::{Drawable, Frame}, NUM_COLS, NUM_ROWS};
+use crate::{config::Config, frame::{Drawable, Frame, Pos, Rect}, grid::Grid, patterns::{March, Pattern, Step}, sprite::{self, Collider, Sprite}, NUM_COLS, NUM_ROWS};
 
+#[derive(Serialize, Deserialize)]
 pub struct Invader {
-    x: usize,
-    y: usize,
+    pub pos: Pos,
+    pub sprite: Sprite,
+    // where the invader was before the army's last step
+    last_pos: Pos,
 }
 
+impl Invader {
+    pub fn new(pos: Pos, sprite: Sprite) -> Self {
+        Self { pos, sprite, last_pos: pos }
+    }
+}
+
+impl Collider for Invader {
+    fn hitbox(&self) -> Rect {
+        self.sprite.hitbox(self.pos)
+    }
+}
+
+// the grids aren't saved, they are rebuilt from the army on load
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct Invaders {
-    pub army: Vec<Invader>,
+    army: Vec<Invader>,
+    // which invader covers which cell, kept in sync with `army`
+    #[serde(skip)]
+    grid: Grid,
+    // same, but for the positions before the last step
+    #[serde(skip)]
+    last_grid: Grid,
+    // whether the army stepped during the last update, for swept collision
+    stepped: bool,
+    #[serde(with = "crate::save::timer")]
     move_timer: Timer,
-    direction: i32,
+    // how much faster each descent makes the army, and the fastest it gets
+    speedup: u64,
+    min_move: u64,
+    #[serde(with = "crate::patterns::boxed")]
+    pattern: Box<dyn Pattern>,
+    // reduced motion, always drawn in the first animation frame
+    #[serde(default)]
+    still: bool,
 }
 
 impl Invaders {
     pub fn new() -> Self {
+        Self::with_sprite(sprite::INVADER)
+    }
+
+    /// the classic formation, spaced so there is a free cell around every sprite
+    pub fn with_sprite(sprite: Sprite) -> Self {
+        let (width, height) = (sprite.width().max(1), sprite.height().max(1));
         let mut army: Vec<Invader> = Vec::new();
-        for x in 0..NUM_COLS {
-            for y in 0..NUM_ROWS {
-                if (x > 1)
-                    && (x < NUM_COLS-2)
-                    && (y > 0)
-                    && (y < 7) // determines num of invaders
-                    && (x % 2 == 0)
-                    && (y % 2 == 0) {
-                        army.push(Invader { x, y });
+        for x in (2..NUM_COLS).step_by(width + 1) {
+            for y in (2..NUM_ROWS).step_by(height + 1) {
+                if (x + width <= NUM_COLS-2)
+                    && (y + height <= 7) { // determines num of invaders
+                        army.push(Invader::new(Pos::new(x, y), sprite));
                 }
             }
         } 
-    Self { army: army, move_timer: Timer::from_millis(2000), direction: 1}
+    Self::from_army(army)
+    }
+
+    /// an army in any formation, marching right
+    pub fn from_army(army: Vec<Invader>) -> Self {
+        let mut invaders = Self {
+            army,
+            grid: Grid::new(NUM_COLS, NUM_ROWS),
+            last_grid: Grid::new(NUM_COLS, NUM_ROWS),
+            stepped: false,
+            move_timer: Timer::from_millis(0),
+            speedup: 0,
+            min_move: 0,
+            pattern: Box::new(March::new()),
+            still: false,
+        };
+        invaders.set_config(&Config::default());
+        invaders.reindex();
+        invaders
+    }
+
+    /// restarts the march timer at the configured speed
+    pub fn set_config(&mut self, config: &Config) {
+        self.move_timer = Timer::from_millis(config.march_ms);
+        self.speedup = config.march_speedup_ms;
+        self.min_move = config.min_march_ms;
+        self.still = config.accessibility.reduced_motion;
+    }
+
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        self.still = on;
+    }
+
+    /// replaces how the army moves from its next step on
+    pub fn set_pattern(&mut self, pattern: Box<dyn Pattern>) {
+        self.pattern = pattern;
+    }
+
+    /// the army steps every `ms` from now on, until it next speeds up
+    pub fn set_march_ms(&mut self, ms: u64) {
+        self.move_timer = Timer::from_millis(ms);
+    }
+
+    /// adds an invader unless it would overlap another, returns whether it did
+    pub fn place(&mut self, invader: Invader) -> bool {
+        if self.grid.find(invader.hitbox()).is_some() {
+            return false;
+        }
+        let idx = self.army.len();
+        self.grid.insert(invader.hitbox(), idx);
+        self.last_grid.insert(invader.sprite.hitbox(invader.last_pos), idx);
+        self.army.push(invader);
+        true
+    }
+
+    /// removes the whole army
+    pub fn clear(&mut self) {
+        self.army.clear();
+        self.reindex();
+    }
+
+    pub fn army(&self) -> &[Invader] {
+        &self.army
+    }
+
+    /// puts the army's next step off by `delay`
+    pub fn delay(&mut self, delay: Duration) {
+        self.move_timer.time_left += delay;
+    }
+
+    /// time until the army's next step
+    pub fn time_to_step(&self) -> Duration {
+        self.move_timer.time_left
+    }
+
+    /// time between two steps at the army's current speed
+    pub fn step_interval(&self) -> Duration {
+        self.move_timer.duration
+    }
+
+    /// rebuilds the grids after the whole army moved
+    fn reindex(&mut self) {
+        self.grid.clear();
+        self.last_grid.clear();
+        for (idx, invader) in self.army.iter().enumerate() {
+            self.grid.insert(invader.hitbox(), idx);
+            self.last_grid.insert(invader.sprite.hitbox(invader.last_pos), idx);
+        }
     }
 
     pub fn update(&mut self, delta: Duration) -> bool {
+        self.stepped = false;
         self.move_timer.update(delta);
         if self.move_timer.ready {
             self.move_timer.reset();
-            let mut downwards = false;
-            if self.direction == -1 {
-                let min_x = self.army.iter().map(|invader| invader.x).min().unwrap_or(0);
-                if min_x == 0 {
-                    self.direction = 1;
-                    downwards = true;
-                }
-            } else {
-                let max_x = self.army.iter().map(|invader| invader.x).max().unwrap_or(0);
-                if max_x == NUM_COLS -1 {
-                    self.direction = -1;
-                    downwards = true;
-                }
+            for invader in self.army.iter_mut() {
+                invader.last_pos = invader.pos;
             }
-            if downwards {
-                let new_duration = max(self.move_timer.duration.as_millis() - 250, 250);
-                self.move_timer = Timer::from_millis(new_duration as u64);
-                for invader in self.army.iter_mut() {
-                    invader.y += 1;
-                }
-            } else {
-                for invader in self.army.iter_mut() {
-                    invader.x = ((invader.x as i32) + self.direction) as usize;
-                }
+            if self.pattern.step(&mut self.army) == Step::Down {
+                let duration = self.move_timer.duration.as_millis() as u64;
+                let new_duration = max(duration.saturating_sub(self.speedup), self.min_move);
+                self.move_timer = Timer::from_millis(new_duration);
             }
+            self.stepped = true;
+            self.reindex();
             return  true;
         }
-        return false
+        false
     }
 
     pub fn all_killed(&self) -> bool {
         self.army.is_empty()
     }
 
-    pub fn reached_bottom(&self) -> bool {
-        self.army.iter().map(|invader| invader.y).max().unwrap_or(0) >= NUM_ROWS -1
+    /// whether an invader is down on `row` or below it
+    pub fn reached_row(&self, row: usize) -> bool {
+        self.army.iter().any(|invader| invader.hitbox().bottom() > row)
     }
 
-    pub fn kill_invader_at(&mut self, x: usize, y: usize) -> bool {
-        if let Some(idx) = self
-        .army
-        .iter()
-        .position(|invader| (invader.x == x) && (invader.y == y)) {
-            self.army.remove(idx);
-            true
-        } else {
-            false
+    pub fn kill_invader_at(&mut self, pos: Pos) -> bool {
+        self.kill_invader_in(Rect::new(pos, 1, 1))
+    }
+
+    /// kills the first invader whose hitbox overlaps `hitbox`
+    pub fn kill_invader_in(&mut self, hitbox: Rect) -> bool {
+        match self.grid.find(hitbox) {
+            Some(idx) => {
+                self.kill(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// kills the first invader a shot ran into while travelling up along `path`,
+    /// counting the cells the army covered both before and after a step it took
+    /// during the last update. returns the cell of the hit.
+    pub fn kill_invader_swept(&mut self, path: Rect) -> Option<Pos> {
+        for y in (path.pos.y..path.bottom()).rev() {
+            for x in path.pos.x..path.right() {
+                let pos = Pos::new(x, y);
+                let found = self.grid.get(pos)
+                    .or_else(|| if self.stepped { self.last_grid.get(pos) } else { None });
+                if let Some(idx) = found {
+                    self.kill(idx);
+                    return Some(pos);
+                }
+            }
+        }
+        None
+    }
+
+    fn kill(&mut self, idx: usize) {
+        let killed = self.army.swap_remove(idx);
+        self.grid.remove(killed.hitbox(), idx);
+        self.last_grid.remove(killed.sprite.hitbox(killed.last_pos), idx);
+        // the last invader was moved into the freed slot
+        if let Some(moved) = self.army.get(idx) {
+            self.grid.insert(moved.hitbox(), idx);
+            self.last_grid.insert(moved.sprite.hitbox(moved.last_pos), idx);
         }
     }
 }
 
+impl Default for Invaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serialize for Invaders {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Invaders::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Invaders {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut invaders = Invaders::deserialize(deserializer)?;
+        invaders.reindex();
+        Ok(invaders)
+    }
+}
+
 impl Drawable for Invaders {
     fn draw(&self, frame: &mut Frame) {
+        // swap animation frames half-way between two steps, unless the army keeps still
+        let halfway = self.move_timer.time_left.as_secs_f32() / self.move_timer.duration.as_secs_f32() <= 0.5;
+        let anim = if halfway && !self.still { 1 } else { 0 };
         for invader in self.army.iter() {
-            frame[invader.x][invader.y] = if ( // "👾";
-                self.move_timer.time_left.as_secs_f32() 
-            / self.move_timer.duration.as_secs_f32()) > 0.5 {
-                "⬢"
-            } else {
-                "⬣"
-            }
+            invader.sprite.draw(frame, invader.pos, anim);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kill_keeps_grid_in_sync() {
+        let mut invaders = Invaders::from_army(vec![
+            Invader::new(Pos::new(1, 1), sprite::INVADER),
+            Invader::new(Pos::new(3, 1), sprite::INVADER_LARGE),
+            Invader::new(Pos::new(8, 1), sprite::INVADER),
+        ]);
+        assert!(invaders.kill_invader_at(Pos::new(1, 1)));
+        assert!(!invaders.kill_invader_at(Pos::new(1, 1)));
+        // the last invader took the freed slot and must still be hittable
+        assert!(invaders.kill_invader_at(Pos::new(8, 1)));
+        assert!(invaders.kill_invader_at(Pos::new(5, 2)));
+        assert!(invaders.all_killed());
+    }
+
+    #[test]
+    fn test_grid_follows_the_army() {
+        let mut invaders = Invaders::from_army(vec![Invader::new(Pos::new(3, 1), sprite::INVADER_LARGE)]);
+        assert!(invaders.update(Duration::from_millis(2001)));
+        // one step right, the old left column is free
+        assert!(!invaders.kill_invader_at(Pos::new(3, 1)));
+        assert!(invaders.kill_invader_at(Pos::new(6, 2)));
+    }
+}
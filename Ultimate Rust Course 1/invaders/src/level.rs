@@ -0,0 +1,244 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bunkers::Bunkers;
+use crate::frame::{Drawable, Frame, Pos, Rect};
+use crate::invaders::Invader;
+use crate::sprite::{self, Sprite};
+use crate::{NUM_COLS, NUM_ROWS};
+
+/// file `invaders edit` works on when no other is given
+pub const LEVEL_FILE: &str = "invaders-level.toml";
+
+/// the sprites an invader can be placed as
+pub const KINDS: [Sprite; 2] = [sprite::INVADER, sprite::INVADER_LARGE];
+
+/// an invader put on the level by hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    #[serde(flatten)]
+    pub pos: Pos,
+    pub sprite: Sprite,
+}
+
+impl Placement {
+    pub fn hitbox(&self) -> Rect {
+        self.sprite.hitbox(self.pos)
+    }
+}
+
+/// a hand-made formation, used for every wave in place of the classic one
+///
+/// ```toml
+/// march_ms = 1500
+/// invaders = [{ x = 2, y = 2, sprite = "invader" }, { x = 4, y = 2, sprite = "invader_large" }]
+/// bunkers = [{ x = 10, y = 15 }, { x = 11, y = 15 }]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    /// time between two army steps at the start of a wave, the config's when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub march_ms: Option<u64>,
+    #[serde(default)]
+    pub invaders: Vec<Placement>,
+    #[serde(default)]
+    pub bunkers: Vec<Pos>,
+}
+
+impl Level {
+    /// the cells things can be placed on: below the score line and above the ship's
+    /// rows, at its largest, so a level plays with either ship
+    pub fn area() -> Rect {
+        let ship = sprite::PLAYER.height().max(sprite::PLAYER_LARGE.height());
+        Rect::new(Pos::new(0, 1), NUM_COLS, NUM_ROWS - 1 - ship)
+    }
+
+    /// whether `hitbox` lies within `area`
+    pub fn fits(hitbox: Rect) -> bool {
+        let area = Self::area();
+        hitbox.pos.x >= area.pos.x && hitbox.pos.y >= area.pos.y && hitbox.right() <= area.right() && hitbox.bottom() <= area.bottom()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        fs::read_to_string(path.as_ref())?.parse()
+    }
+
+    /// like `load`, but a missing file is an empty level
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        match Self::load(path) {
+            Err(LevelError::Io(err)) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            other => other,
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LevelError> {
+        let text = toml::to_string(self).map_err(|err| LevelError::Parse(err.to_string()))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// checks the level can be played: everything in the area, nothing overlapping
+    /// and at least one invader
+    pub fn validate(self) -> Result<Self, LevelError> {
+        if self.invaders.is_empty() {
+            return Err(LevelError::Invalid("there are no invaders".into()));
+        }
+        if self.march_ms == Some(0) {
+            return Err(LevelError::Invalid("march_ms must be above 0".into()));
+        }
+        for (idx, invader) in self.invaders.iter().enumerate() {
+            if !KINDS.contains(&invader.sprite) {
+                return Err(LevelError::Invalid(format!("`{}` is not an invader", invader.sprite.name)));
+            }
+            if !Self::fits(invader.hitbox()) {
+                return Err(LevelError::Invalid(format!("the invader at {},{} is off the field", invader.pos.x, invader.pos.y)));
+            }
+            if self.invaders[..idx].iter().any(|other| other.hitbox().intersects(&invader.hitbox())) {
+                return Err(LevelError::Invalid(format!("the invader at {},{} overlaps another", invader.pos.x, invader.pos.y)));
+            }
+        }
+        for (idx, bunker) in self.bunkers.iter().enumerate() {
+            if !Self::fits(Rect::new(*bunker, 1, 1)) || self.occupied(Rect::new(*bunker, 1, 1)) {
+                return Err(LevelError::Invalid(format!("the bunker at {},{} is off the field or under an invader", bunker.x, bunker.y)));
+            }
+            if self.bunkers[..idx].contains(bunker) {
+                return Err(LevelError::Invalid(format!("there are two bunkers at {},{}", bunker.x, bunker.y)));
+            }
+        }
+        Ok(self)
+    }
+
+    fn occupied(&self, hitbox: Rect) -> bool {
+        self.invaders.iter().any(|invader| invader.hitbox().intersects(&hitbox))
+    }
+
+    /// puts an invader at `pos` if it fits there without overlapping anything
+    pub fn place(&mut self, pos: Pos, sprite: Sprite) -> bool {
+        let hitbox = sprite.hitbox(pos);
+        if !Self::fits(hitbox) || self.occupied(hitbox) || self.bunkers.iter().any(|bunker| hitbox.contains(*bunker)) {
+            return false;
+        }
+        self.invaders.push(Placement { pos, sprite });
+        true
+    }
+
+    /// puts a bunker block at `pos` if the cell is free
+    pub fn add_bunker(&mut self, pos: Pos) -> bool {
+        let hitbox = Rect::new(pos, 1, 1);
+        if !Self::fits(hitbox) || self.occupied(hitbox) || self.bunkers.contains(&pos) {
+            return false;
+        }
+        self.bunkers.push(pos);
+        true
+    }
+
+    /// removes whatever covers `pos`, returns whether there was anything
+    pub fn remove_at(&mut self, pos: Pos) -> bool {
+        let before = self.invaders.len() + self.bunkers.len();
+        self.invaders.retain(|invader| !invader.hitbox().contains(pos));
+        self.bunkers.retain(|bunker| *bunker != pos);
+        self.invaders.len() + self.bunkers.len() != before
+    }
+
+    pub fn army(&self) -> Vec<Invader> {
+        self.invaders.iter().map(|invader| Invader::new(invader.pos, invader.sprite)).collect()
+    }
+
+    pub fn bunkers(&self) -> Bunkers {
+        Bunkers::new(self.bunkers.clone())
+    }
+}
+
+impl FromStr for Level {
+    type Err = LevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|err| LevelError::Parse(err.to_string()))
+    }
+}
+
+impl Drawable for Level {
+    fn draw(&self, frame: &mut Frame) {
+        for invader in self.invaders.iter() {
+            invader.sprite.draw(frame, invader.pos, 0);
+        }
+        self.bunkers().draw(frame);
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "could not access level: {}", err),
+            LevelError::Parse(msg) => write!(f, "could not parse level: {}", msg),
+            LevelError::Invalid(msg) => write!(f, "invalid level: {}", msg),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        LevelError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_editing() {
+        let mut level = Level::default();
+        assert!(level.place(Pos::new(2, 2), sprite::INVADER_LARGE));
+        // overlaps the large invader
+        assert!(!level.place(Pos::new(4, 3), sprite::INVADER));
+        assert!(!level.add_bunker(Pos::new(3, 3)));
+        // score line and ship row stay free
+        assert!(!level.place(Pos::new(8, 0), sprite::INVADER));
+        assert!(!level.add_bunker(Pos::new(8, NUM_ROWS - 1)));
+        // and so does the large ship's top row
+        assert!(!level.add_bunker(Pos::new(8, NUM_ROWS - 2)));
+        assert!(level.add_bunker(Pos::new(8, 15)));
+        assert!(level.remove_at(Pos::new(4, 3)));
+        assert!(!level.remove_at(Pos::new(4, 3)));
+        assert_eq!((level.invaders.len(), level.bunkers.len()), (0, 1));
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let mut level = Level { march_ms: Some(900), ..Level::default() };
+        level.place(Pos::new(2, 2), sprite::INVADER);
+        level.place(Pos::new(4, 2), sprite::INVADER_LARGE);
+        level.add_bunker(Pos::new(10, 15));
+        let text = toml::to_string(&level).unwrap();
+        assert_eq!(text.parse::<Level>().unwrap().validate().unwrap(), level);
+    }
+
+    #[test]
+    fn test_bad_levels_are_rejected() {
+        assert!(matches!("".parse::<Level>().unwrap().validate(), Err(LevelError::Invalid(_))));
+        assert!(matches!("invaders = [{ x = 2, y = 2, sprite = \"ship\" }]".parse::<Level>(), Err(LevelError::Parse(_))));
+        let shot = "invaders = [{ x = 2, y = 2, sprite = \"shot\" }]".parse::<Level>().unwrap();
+        assert!(matches!(shot.validate(), Err(LevelError::Invalid(_))));
+        let low = "invaders = [{ x = 2, y = 19, sprite = \"invader\" }]".parse::<Level>().unwrap();
+        assert!(matches!(low.validate(), Err(LevelError::Invalid(_))));
+        let twice = "invaders = [{ x = 2, y = 2, sprite = \"invader\" }]\nbunkers = [{ x = 10, y = 15 }, { x = 10, y = 15 }]";
+        assert!(matches!(twice.parse::<Level>().unwrap().validate(), Err(LevelError::Invalid(_))));
+    }
+}
@@ -1,8 +1,35 @@
+pub mod bot;
+pub mod bunkers;
+pub mod cast;
+pub mod cli;
+pub mod config;
+pub mod debug;
+pub mod editor;
+pub mod env;
+pub mod events;
 pub mod frame;
+pub mod game;
+pub mod grid;
+pub mod reload;
 pub mod render;
+pub mod replay;
+pub mod rng;
 pub mod player;
 pub mod shot;
+pub mod snapshot;
+pub mod sprite;
+pub mod stats;
+pub mod synth;
 pub mod invaders;
+pub mod level;
+pub mod logging;
+pub mod menu;
+pub mod music;
+pub mod particles;
+pub mod patterns;
+pub mod save;
+pub mod script;
+pub mod terminal;
 
 pub const NUM_ROWS: usize = 20;
 pub const NUM_COLS: usize = 40;
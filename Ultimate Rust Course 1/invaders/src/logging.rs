@@ -0,0 +1,154 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// the log is kept here, in the working directory. the terminal is the game's.
+pub const LOG_FILE: &str = "invaders.log";
+
+/// records below this level are dropped unless `--log-level` says otherwise
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// the log starts over in a new file once it grows past this
+pub const MAX_SIZE: u64 = 1024 * 1024;
+
+/// older logs kept, `invaders.log.1` is the newest of them
+pub const KEEP: usize = 3;
+
+/// writes records to a file, one line each, moving full files aside as
+/// `<file>.1`, `<file>.2` and so on
+pub struct FileLogger {
+    path: PathBuf,
+    level: LevelFilter,
+    max_size: u64,
+    keep: usize,
+    // the open file and how much is in it
+    file: Mutex<Option<(File, u64)>>,
+}
+
+impl FileLogger {
+    pub fn new(path: impl Into<PathBuf>, level: LevelFilter) -> Self {
+        Self { path: path.into(), level, max_size: MAX_SIZE, keep: KEEP, file: Mutex::new(None) }
+    }
+
+    /// rotates once the file holds `max_size` bytes, keeping `keep` old files
+    pub fn with_rotation(mut self, max_size: u64, keep: usize) -> Self {
+        self.max_size = max_size;
+        self.keep = keep;
+        self
+    }
+
+    fn open(&self) -> io::Result<(File, u64)> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
+    // `<file>.n`
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    // shifts every old file one up, the oldest falls off
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        for n in (1..self.keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                fs::rename(from, self.rotated(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+
+    fn write(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+        if file.as_ref().is_some_and(|(_, size)| *size >= self.max_size) {
+            *file = None;
+            self.rotate()?;
+        }
+        if file.is_none() {
+            *file = Some(self.open()?);
+        }
+        if let Some((file, size)) = file.as_mut() {
+            file.write_all(line.as_bytes())?;
+            *size += line.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let line = format!("{}.{:03} {:<5} {}: {}\n", time.as_secs(), time.subsec_millis(), record.level(), record.target(), record.args());
+        // nowhere to report a failing log to, the game goes on without it
+        let _ = self.write(&line);
+    }
+
+    fn flush(&self) {
+        if let Some((file, _)) = self.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// sends the `log` macros to `path` from here on, at `level` and more severe
+pub fn init(path: impl AsRef<Path>, level: LevelFilter) -> Result<(), Box<dyn std::error::Error>> {
+    let logger = FileLogger::new(path.as_ref(), level);
+    if level != LevelFilter::Off {
+        // fail now, not silently on the first record
+        logger.open().map_err(|err| format!("{}: {}", path.as_ref().display(), err))?;
+    }
+    log::set_boxed_logger(Box::new(logger))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use log::Level;
+
+    fn record(logger: &FileLogger, level: Level, message: &str) {
+        logger.log(&Record::builder().level(level).target("invaders::test").args(format_args!("{}", message)).build());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("invaders-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+        let logger = FileLogger::new(&path, LevelFilter::Info).with_rotation(64, 2);
+        record(&logger, Level::Debug, "too detailed");
+        assert!(!path.exists());
+        for n in 0..8 {
+            record(&logger, Level::Warn, &format!("message {}", n));
+        }
+        logger.flush();
+
+        let current = fs::read_to_string(&path).unwrap();
+        assert!(current.ends_with("WARN  invaders::test: message 7\n"), "{}", current);
+        assert!(logger.rotated(1).exists() && logger.rotated(2).exists());
+        // the oldest ones are gone
+        assert!(!logger.rotated(3).exists());
+        let kept: String = [logger.rotated(2), logger.rotated(1), path.clone()].iter().map(|path| fs::read_to_string(path).unwrap()).collect();
+        assert!(!kept.contains("message 0") && kept.contains("message 5"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
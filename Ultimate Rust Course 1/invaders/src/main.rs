@@ -1,109 +1,809 @@
-use std::{error::Error, sync::mpsc, thread, time::{Duration, Instant}};
-use invaders::{frame::{self, new_frame, Drawable}, invaders::Invaders, player::Player, render};
-use rusty_audio::Audio;
-use std::io;
-use crossterm::{cursor::{Hide, Show}, event::{self, Event, KeyCode}, terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}, ExecutableCommand};
+use std::{error::Error, process, sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Sender}, Arc, Mutex}, thread::{self, JoinHandle}, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+use clap::Parser;
+use invaders::{bot::Bot, cast::CastWriter, cli::{Cli, Command, DisplayArgs, PlayArgs}, debug::DebugOverlay, config::{Config, ConfigError, ConfigFile, CONFIG_FILE}, editor::{EditAction, Editor}, frame::{self, new_frame, Drawable, Frame, Pos}, events::{self, GameEvent, Subscriber}, game::{Action, Game, Reload, Status, TICK}, level::Level, logging::{self, DEFAULT_LEVEL, LOG_FILE}, menu::Menu, particles::Particles, reload::{Banner, Watcher}, render::{self, Theme}, replay::{Playback, Recorder, Replay, REPLAY_FILE}, rng, save::{self, SAVE_FILE}, script::Script, snapshot, stats::{Notifications, Record, Score, Session, NOTICE_TIME, STATS_FILE}, music::{Note, Soundtrack}, synth::{self, Effect, SAMPLE_RATE}, terminal::{self, TerminalGuard}, NUM_COLS, NUM_ROWS};
+use rodio::{buffer::SamplesBuffer, OutputStream, OutputStreamHandle, Sink};
+use rusty_time::timer::Timer;
+use std::{collections::HashMap, fs::File, io::{self, BufWriter, Write}, path::PathBuf};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
+use log::{debug, error, info, warn};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // add all audio files
-    let mut audio = Audio::new();
-
-    audio.add("explode", "explode.wav"); 
-    audio.add("lose", "lose.wav"); 
-    audio.add("move", "move.wav"); 
-    audio.add("pew", "pew.wav"); 
-    audio.add("startup", "startup.wav"); 
-    audio.add("win", "win.wav"); 
-
-    audio.play("startup");
-
-    // terminal
-    let mut stdout = io::stdout();
-    terminal::enable_raw_mode()?;
-    stdout.execute(EnterAlternateScreen)?;
-    stdout.execute(Hide)?;
-
-    // rendering loop in seperate thread
-    let (render_tx, render_rx) = mpsc::channel();
-    let render_handle = thread::spawn(move || {
-        let mut last_frame = frame::new_frame();
-        let mut stdout = io::stdout();
-        render::render(&mut stdout, &last_frame, &last_frame, true);
-        loop {
-            let curr_frame = match render_rx.recv() {
-                Ok(x) => x,
-                Err(_) => break,
+    // bad arguments are reported by clap, before the terminal is taken over
+    let command = Cli::parse().command();
+    // raw mode owns the terminal, so the log goes to a file
+    if let Some(display) = command.display() {
+        if let Err(err) = logging::init(LOG_FILE, display.log_level.unwrap_or(DEFAULT_LEVEL)) {
+            eprintln!("{}, not logging", err);
+        }
+    }
+
+    // lifetime statistics and achievements, a broken file means this run isn't tracked
+    let stats = match Record::load_or_default(STATS_FILE) {
+        Ok(record) => Some(record),
+        Err(err) => {
+            eprintln!("{}: {}, statistics are not tracked", STATS_FILE, err);
+            None
+        }
+    };
+
+    match command {
+        Command::Play(args) => run(args, stats),
+        // the error was printed above, there is nothing to show
+        Command::Scores => {
+            let Some(record) = stats else { process::exit(1) };
+            println!("{}", record.scores);
+            Ok(())
+        }
+        Command::Stats => {
+            let Some(record) = stats else { process::exit(1) };
+            println!("{}", record);
+            Ok(())
+        }
+        Command::Replay { file, display } => {
+            let replay = match Replay::load(&file) {
+                Ok(replay) => replay,
+                Err(err) => {
+                    eprintln!("{}: {}", file.display(), err);
+                    process::exit(1);
+                }
+            };
+            watch(replay, display)
+        }
+        Command::Edit { file, display } => edit(file, display),
+    }
+}
+
+/// the sound effects, generated at start-up, and the army's march,
+/// or nothing at all with `--no-sound` or without an audio device
+struct Sound {
+    // the stream stops playing when dropped
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    // a few effects can overlap, each plays on a channel that is free
+    channels: Vec<Sink>,
+    // the march has a channel of its own, so effects never hold up the beat
+    march: Option<Sink>,
+    clips: HashMap<Effect, Vec<f32>>,
+    // whether to cue invaders above the ship
+    cues: bool,
+    // march notes, generated the first time they play
+    notes: HashMap<Note, Vec<f32>>,
+    soundtrack: Soundtrack,
+}
+
+impl Sound {
+    const CHANNELS: usize = 4;
+
+    fn new(enabled: bool) -> Self {
+        let output = if enabled { OutputStream::try_default().ok() } else { None };
+        if enabled && output.is_none() {
+            warn!("no audio device, playing without sound");
+        }
+        let (channels, march) = match &output {
+            Some((_, handle)) => (
+                (0..Self::CHANNELS).filter_map(|_| Sink::try_new(handle).ok()).collect(),
+                Sink::try_new(handle).ok(),
+            ),
+            None => (Vec::new(), None),
+        };
+        let clips = if channels.is_empty() {
+            HashMap::new()
+        } else {
+            Effect::ALL.iter().map(|effect| (*effect, effect.samples())).collect()
+        };
+        Self { output, channels, march, clips, cues: false, notes: HashMap::new(), soundtrack: Soundtrack::default() }
+    }
+
+    /// picks up the march where `game` is, at the start of its current wave
+    fn start(&mut self, game: &Game) {
+        self.soundtrack = Soundtrack::new(game.config.march_notes.clone(), game.wave);
+        self.cues = game.config.accessibility.audio_cues;
+    }
+
+    // with every channel busy the effect is dropped, queued it would lag behind the game
+    fn play(&mut self, effect: Effect) {
+        let (Some(channel), Some(samples)) = (self.channels.iter().find(|channel| channel.empty()), self.clips.get(&effect)) else {
+            return;
+        };
+        channel.append(SamplesBuffer::new(1, SAMPLE_RATE, samples.clone()));
+    }
+
+    // the next note of the march, one per army step, so it speeds up with the army
+    fn step(&mut self) {
+        let (Some(march), Some(note)) = (&self.march, self.soundtrack.next_note()) else {
+            return;
+        };
+        let samples = self.notes.entry(note).or_insert_with(|| synth::march(note));
+        march.append(SamplesBuffer::new(1, SAMPLE_RATE, samples.clone()));
+    }
+
+    fn wait(&self) {
+        if self.output.is_some() {
+            for channel in self.channels.iter().chain(self.march.iter()) {
+                channel.sleep_until_end();
+            }
+        }
+    }
+}
+
+impl Subscriber for Sound {
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ShotFired { .. } => self.play(Effect::Pew),
+            // make sound of invaders moving
+            GameEvent::ArmyMoved => self.step(),
+            GameEvent::InvaderAbove if self.cues => self.play(Effect::Cue),
+            // make sound of a hit, on an invader or a bunker
+            GameEvent::InvaderKilled { .. } | GameEvent::BunkerHit { .. } => self.play(Effect::Explode),
+            // announce the next wave
+            GameEvent::WaveCleared { .. } => {
+                self.play(Effect::Startup);
+                self.soundtrack.next_wave();
+            }
+            GameEvent::GameOver { won: true } => self.play(Effect::Win),
+            GameEvent::GameOver { won: false } => self.play(Effect::Lose),
+            GameEvent::ShotMoved { .. } | GameEvent::InvaderAbove | GameEvent::ScriptFailed | GameEvent::PlayerHit { .. } => {}
+        }
+    }
+}
+
+/// the terminal in game mode, with frames drawn on a render thread
+struct Screen {
+    render_tx: Sender<Frame>,
+    render_handle: JoinHandle<()>,
+    // the colours frames are drawn in, they can change while the screen is up
+    theme: Arc<Mutex<Theme>>,
+    // restores the terminal when dropped, after the render thread is done with it
+    _terminal: TerminalGuard,
+}
+
+impl Screen {
+    fn start(display: &DisplayArgs) -> Result<Self, Box<dyn Error>> {
+        let theme = display.theme.unwrap_or_default();
+        // everything drawn goes to the terminal, and to the cast file if there is one
+        let mut out: Box<dyn Write + Send> = Box::new(io::stdout());
+        if let Some(path) = &display.cast {
+            let file = File::create(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            out = Box::new(CastWriter::new(out, BufWriter::new(file), NUM_COLS, NUM_ROWS)?);
+        }
+
+        // terminal, restored on drop and on panic
+        let terminal = TerminalGuard::new()?;
+
+        // rendering loop in seperate thread
+        let (render_tx, render_rx) = mpsc::channel();
+        let shared = Arc::new(Mutex::new(theme));
+        let current = shared.clone();
+        let render_handle = thread::spawn(move || {
+            let mut last_frame = frame::new_frame();
+            let mut drawn = theme;
+            render::render(&mut out, &last_frame, &last_frame, true, drawn);
+            while let Ok(curr_frame) = render_rx.recv() {
+                // a new theme repaints everything
+                let theme = *current.lock().unwrap();
+                render::render(&mut out, &last_frame, &curr_frame, theme != drawn, theme);
+                drawn = theme;
+                last_frame = curr_frame;
+            }
+        });
+        Ok(Self { render_tx, render_handle, theme: shared, _terminal: terminal })
+    }
+
+    fn theme(&self) -> Theme {
+        *self.theme.lock().unwrap()
+    }
+
+    /// the next frame is drawn in `theme`
+    fn set_theme(&self, theme: Theme) {
+        *self.theme.lock().unwrap() = theme;
+    }
+
+    fn close(self) -> Result<(), Box<dyn Error>> {
+        drop(self.render_tx);
+        if self.render_handle.join().is_err() {
+            error!("render thread panicked");
+            return Err("render thread panicked".into());
+        }
+        Ok(())
+    }
+}
+
+/// `invaders play`: the title screen, then a game
+fn run(mut args: PlayArgs, stats: Option<Record>) -> Result<(), Box<dyn Error>> {
+    // settings, read before the terminal is taken over so errors stay readable.
+    // flags win over the config file.
+    let config = match load_config(&args) {
+        Ok((config, theme)) => {
+            args.display.theme = theme;
+            config
+        }
+        Err(err) => {
+            eprintln!("{}: {}", CONFIG_FILE, err);
+            process::exit(1);
+        }
+    };
+    // a hand-made formation, a broken one is as fatal as a broken config
+    let level = match &args.level {
+        Some(path) => match Level::load(path).and_then(Level::validate) {
+            Ok(level) => Some(level),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    // a wave script, one that doesn't compile is as fatal as a broken level
+    let script = match &args.script {
+        Some(path) => match Script::load(path) {
+            Ok(script) => Some(script),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+    // a game quit half-way last time, a broken save is reported and skipped
+    let saved = match save::load_if_present(SAVE_FILE) {
+        Ok(saved) => saved,
+        Err(err) => {
+            eprintln!("{}: {}, starting a new game", SAVE_FILE, err);
+            None
+        }
+    };
+
+    // a new game is repeatable with the seed it shows at the end
+    let seed = args.seed.unwrap_or_else(rng::seed_from_time);
+    info!("new game with seed {}", seed);
+    let mut new_game = Game::with_seed(config.clone(), seed);
+    if let Some(level) = level {
+        new_game.set_level(level);
+    }
+    if let Some(script) = script {
+        new_game.set_script(script);
+    }
+
+    let mut sound = Sound::new(!args.display.no_sound);
+    sound.play(Effect::Startup);
+
+    // edits from here on reach the game, also those made on the title screen
+    let mut watcher = Watcher::new().watch(Source::Config, CONFIG_FILE);
+    if let Some(path) = &args.level {
+        watcher = watcher.watch(Source::Level, path);
+    }
+    if let Some(path) = &args.script {
+        watcher = watcher.watch(Source::Script, path);
+    }
+
+    let quit = terminal::quit_signal()?;
+    let screen = Screen::start(&args.display)?;
+
+    let mut played = None;
+    if let Some(mut game) = title_screen(&config, new_game, saved, &screen.render_tx, &quit)? {
+        // a saved game looks and sounds the way the player wants it now
+        game.set_accessibility(config.accessibility);
+        let mut record = stats.clone().unwrap_or_default();
+        played = Some(play(game, &mut record, &mut sound, &screen, &args, watcher, &quit)?);
+        if stats.is_some() {
+            record.save(STATS_FILE)?;
+        }
+    }
+
+    // clean up, the terminal is restored once the screen is closed
+    screen.close()?;
+    sound.wait();
+    // the game only said the script stopped, the whole error fits here
+    if let Some(err) = played.as_ref().and_then(|game: &Game| game.script.as_ref()).and_then(Script::error) {
+        eprintln!("{}", err);
+    }
+    Ok(())
+}
+
+/// the theme picked with `--theme`, or in the config file, or the high-contrast one
+/// if that's asked for
+fn theme(display: &DisplayArgs, file: &ConfigFile) -> Option<Theme> {
+    let high_contrast = display.accessibility.apply(file.accessibility).high_contrast;
+    display.theme.or(file.theme).or(Some(Theme::Contrast).filter(|_| high_contrast))
+}
+
+/// the config file with the flags on top, and the theme to draw in
+fn load_config(args: &PlayArgs) -> Result<(Config, Option<Theme>), ConfigError> {
+    let mut file = ConfigFile::load_or_default(CONFIG_FILE)?;
+    file.difficulty = args.difficulty.or(file.difficulty);
+    file.size = args.size.or(file.size);
+    file.accessibility = args.display.accessibility.apply(file.accessibility);
+    Ok((file.build()?, theme(&args.display, &file)))
+}
+
+/// the files a running game follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Config,
+    Level,
+    Script,
+}
+
+// what a watched file holds now, as a change to the game. the theme comes with the config.
+fn reload(source: Source, args: &PlayArgs) -> Result<(Reload, Option<Theme>), String> {
+    match (source, &args.level, &args.script) {
+        (Source::Config, _, _) => load_config(args)
+            .map(|(config, theme)| (Reload::Config(config), Some(theme.unwrap_or_default())))
+            .map_err(|err| format!("{}: {}", CONFIG_FILE, err)),
+        (Source::Level, Some(path), _) => Level::load(path)
+            .and_then(Level::validate)
+            .map(|level| (Reload::Level(level), None))
+            .map_err(|err| format!("{}: {}", path.display(), err)),
+        (Source::Script, _, Some(path)) => Script::load(path)
+            .map(|script| (Reload::Script(Box::new(script)), None))
+            .map_err(|err| format!("{}: {}", path.display(), err)),
+        _ => Err(format!("{:?} is not watched", source)),
+    }
+}
+
+// the config file with only the display flags, for the commands that don't play.
+// a broken one means the defaults.
+fn display_config(display: &mut DisplayArgs) -> ConfigFile {
+    let mut file = ConfigFile::load_or_default(CONFIG_FILE).unwrap_or_default();
+    display.theme = theme(display, &file);
+    file.accessibility = display.accessibility.apply(file.accessibility);
+    file
+}
+
+// ctrl-c arrives as a key in raw mode, not as SIGINT
+fn is_ctrl_c(key_event: &KeyEvent) -> bool {
+    key_event.code == KeyCode::Char('c') && key_event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// what can be picked on the title screen
+#[derive(Clone, Copy)]
+enum TitleItem {
+    Play,
+    Continue,
+    Quit,
+}
+
+/// what can be picked while the game is paused
+#[derive(Clone, Copy)]
+enum PauseItem {
+    Resume,
+    Quit,
+}
+
+// where a left click landed, if the event is one
+fn left_click(event: &Event) -> Option<Pos> {
+    match *event {
+        Event::Mouse(MouseEvent::Down(MouseButton::Left, x, y, _)) => Some(Pos::new(x as usize, y as usize)),
+        _ => None,
+    }
+}
+
+/// attract mode: the bot plays a demo game behind the title.
+/// returns the game to play, `new_game` or the saved one, or `None` to quit.
+fn title_screen(config: &Config, new_game: Game, mut saved: Option<Game>, render_tx: &Sender<Frame>, quit: &AtomicBool) -> Result<Option<Game>, Box<dyn Error>> {
+    let mut demo = Game::with_config(config.clone());
+    let mut bot = Bot::new();
+    let mut instant = Instant::now();
+    let mut menu = Menu::new(11).item("space to play", TitleItem::Play);
+    if saved.is_some() {
+        menu = menu.item("c to continue", TitleItem::Continue);
+    }
+    let menu = menu.item("q to quit", TitleItem::Quit);
+    // SIGINT or SIGTERM
+    while !quit.load(Ordering::SeqCst) {
+        let delta = instant.elapsed();
+        instant = Instant::now();
+
+        // input handling, keys or a click on a menu item
+        while event::poll(Duration::default())? {
+            let event = event::read()?;
+            let item = match event {
+                Event::Key(key_event) => match key_event.code {
+                    KeyCode::Char(' ') | KeyCode::Enter => Some(TitleItem::Play),
+                    KeyCode::Char('c') if !is_ctrl_c(&key_event) => Some(TitleItem::Continue),
+                    KeyCode::Esc | KeyCode::Char('q') => Some(TitleItem::Quit),
+                    _ if is_ctrl_c(&key_event) => Some(TitleItem::Quit),
+                    _ => None,
+                },
+                _ => left_click(&event).and_then(|pos| menu.at(pos)),
             };
-            render::render(&mut stdout, &last_frame, &curr_frame, false);
-            last_frame = curr_frame;
+            match item {
+                Some(TitleItem::Play) => return Ok(Some(new_game)),
+                Some(TitleItem::Continue) if saved.is_some() => return Ok(saved.take()),
+                Some(TitleItem::Quit) => return Ok(None),
+                _ => {}
+            }
+        }
+        // updates, starting over once the demo game is decided
+        if let Some(action) = bot.update(delta, &demo) {
+            demo.act(action);
+        }
+        demo.update(delta);
+        if demo.status() != Status::Playing {
+            demo = Game::with_config(config.clone());
+            bot = Bot::new();
+        }
+
+        // draw and render
+        let mut curr_frame = new_frame();
+        demo.draw(&mut curr_frame);
+        curr_frame.text_centered(9, " I N V A D E R S ");
+        menu.draw(&mut curr_frame);
+        if render_tx.send(curr_frame).is_err() {
+            break;
         }
-    });
+        thread::sleep(Duration::from_millis(2));
+    }
+    Ok(None)
+}
 
+/// plays `game` to the end and adds it to `record`, the game is recorded to `REPLAY_FILE`.
+/// quitting early saves it, so it can be continued next launch. `s` writes the screen
+/// to snapshot files in the screen's colours, `p` pauses, `d` shows the debug overlay.
+/// a click steers the ship to the clicked column and fires.
+fn play(mut game: Game, record: &mut Record, sound: &mut Sound, screen: &Screen, args: &PlayArgs, mut watcher: Watcher<Source>, quit: &AtomicBool) -> Result<Game, Box<dyn Error>> {
     // create objects
-    let mut player = Player::new();
     let mut instant = Instant::now();
-    let mut invaders = Invaders::new();
+    // real time not yet simulated, the game advances in fixed ticks so it can be replayed
+    let mut lag = Duration::default();
+    let mut recorder = Recorder::new(&game)?;
+    sound.start(&game);
+    let mut particles = Particles::new();
+    particles.set_reduced_motion(game.config.accessibility.reduced_motion);
+    let mut session = Session::new();
+    let mut notifications = Notifications::new();
+    // set once the game is decided, the loop keeps going until the effects are done
+    let mut game_over = false;
+    // the end screen stays up at least this long
+    let mut end_timer = Timer::from_millis(2500);
+    // set by the snapshot key, the frame is written once it is drawn
+    let mut take_snapshot = false;
+    // what became of the last snapshot or the script, shown for a while
+    let mut notice: Option<(String, Timer)> = None;
+    // files that were edited but don't load, the game goes on as it was
+    let mut banner = Banner::new();
+    let mut overlay = DebugOverlay::new();
+    // nothing moves while paused
+    let mut paused = false;
+    let pause_menu = Menu::new(11).item("p to resume", PauseItem::Resume).item("q to quit", PauseItem::Quit);
+    // the column the ship heads for after a click, one step per tick, and whether
+    // the click's shot is still to go once it's there
+    let mut target: Option<usize> = None;
+    let mut fire_at_target = false;
 
     // game loop
     'gameloop: loop {
+        // SIGINT or SIGTERM
+        if quit.load(Ordering::SeqCst) {
+            break 'gameloop;
+        }
         // per frame init
         let delta = instant.elapsed();
         instant = Instant::now();
         let mut curr_frame = new_frame();
 
+        // edited files, before this tick's input so replays apply them in the same order
+        for source in watcher.update(delta) {
+            match reload(source, args) {
+                Ok((reload, theme)) => {
+                    info!("reloaded {:?}", source);
+                    banner.set(source, None);
+                    recorder.reload(reload.clone());
+                    game.reload(reload);
+                    particles.set_reduced_motion(game.config.accessibility.reduced_motion);
+                    sound.cues = game.config.accessibility.audio_cues;
+                    if let Some(theme) = theme {
+                        debug!("drawing in the {} theme", theme);
+                        screen.set_theme(theme);
+                    }
+                }
+                Err(err) => {
+                    warn!("{}", err);
+                    banner.set(source, Some(err));
+                }
+            }
+        }
+
         // input handling
         while event::poll(Duration::default())? {
-            if let Event::Key(key_event) = event::read()? {
-                match key_event.code {
+            let event = event::read()?;
+            let action = match event {
+                Event::Key(key_event) => match key_event.code {
                     // moving player
-                    KeyCode::Left => player.move_left(),
-                    KeyCode::Right => player.move_right(),
+                    KeyCode::Left => Some(Action::Left),
+                    KeyCode::Right => Some(Action::Right),
                     // shooting
-                    KeyCode::Char(' ') | KeyCode::Enter => {
-                        if player.shoot() { audio.play("pew"); }
+                    KeyCode::Char(' ') | KeyCode::Enter => Some(Action::Fire),
+                    KeyCode::Char('s') => {
+                        take_snapshot = true;
+                        None
+                    }
+                    KeyCode::Char('p') => {
+                        paused = !paused && !game_over;
+                        None
+                    }
+                    KeyCode::Char('d') => {
+                        overlay.toggle();
+                        None
                     }
-                    // closing game
-                    KeyCode::Esc | KeyCode::Char('q') => {audio.play("lose");
-                    break 'gameloop; 
+                    // closing game, quietly: it is saved to be continued
+                    KeyCode::Esc | KeyCode::Char('q') => break 'gameloop,
+                    _ if is_ctrl_c(&key_event) => break 'gameloop,
+                    _ => None,
+                },
+                _ if paused => match left_click(&event).and_then(|pos| pause_menu.at(pos)) {
+                    Some(PauseItem::Resume) => {
+                        paused = false;
+                        None
                     }
-                    _ => {}
+                    Some(PauseItem::Quit) => break 'gameloop,
+                    None => None,
+                },
+                // steering with the mouse, a click also fires from the clicked column
+                Event::Mouse(MouseEvent::Down(MouseButton::Left, x, _, _)) => {
+                    target = Some(x as usize);
+                    fire_at_target = true;
+                    None
+                }
+                Event::Mouse(MouseEvent::Drag(MouseButton::Left, x, _, _)) => {
+                    target = Some(x as usize);
+                    None
+                }
+                _ => None,
+            };
+            // the keys take over from the mouse
+            if matches!(action, Some(Action::Left | Action::Right)) {
+                target = None;
+                fire_at_target = false;
+            }
+            if let Some(action) = action.filter(|_| !game_over && !paused) {
+                if game.act(action) {
+                    recorder.record(action);
                 }
             }
         }
         // updates
-        player.update(delta);
-        // make sound of invaders moving
-        if invaders.update(delta) { audio.play("move"); }
-        // make sound of hitting invader
-        if player.detect_hits(&mut invaders) { audio.play("explode"); }
+        if !paused {
+            particles.update(delta);
+            notifications.update(delta);
+        }
+        if let Some((_, timer)) = &mut notice {
+            timer.update(delta);
+            if timer.ready {
+                notice = None;
+            }
+        }
+        if game_over {
+            end_timer.update(delta);
+        } else if !paused {
+            lag += delta;
+        }
+        while lag >= TICK && game.status() == Status::Playing {
+            lag -= TICK;
+            // a step towards the clicked column, given up on at the edge
+            if let Some(x) = target {
+                match game.steer_towards(x) {
+                    Some(action) if game.act(action) => recorder.record(action),
+                    // there, or as close as the ship gets
+                    _ => {
+                        target = None;
+                        if std::mem::take(&mut fire_at_target) && game.act(Action::Fire) {
+                            recorder.record(Action::Fire);
+                        }
+                    }
+                }
+            }
+            let events = game.update(TICK);
+            recorder.tick();
+            events::publish(&events, &mut [sound, &mut particles, &mut session, &mut overlay]);
+            for achievement in record.unlock(&session.stats) { notifications.push(achievement); }
+            if events.contains(&GameEvent::ScriptFailed) {
+                let err = game.script.as_ref().and_then(Script::error);
+                if let Some(err) = err {
+                    error!("{}", err);
+                }
+                let line = err.and_then(|err| err.line());
+                let text = match line {
+                    Some(line) => format!(" script stopped on line {} ", line),
+                    None => " script stopped ".to_string(),
+                };
+                notice = Some((text, Timer::from_millis(NOTICE_TIME.as_millis() as u64)));
+            }
+        }
+        game_over = game.status() != Status::Playing;
+        overlay.update(&game, delta);
 
-        // draw and render
-        let drawables: Vec<&dyn Drawable> = vec![&player, &invaders];
+        // draw and render, the ship is gone once it was hit
+        let drawables: Vec<&dyn Drawable> = if game.status() == Status::Lost {
+            vec![&particles, &game.invaders, &notifications]
+        } else {
+            vec![&particles, &game, &notifications]
+        };
         for drawable in drawables { drawable.draw(&mut curr_frame); }
-        let _ = render_tx.send(curr_frame);
+        banner.draw(&mut curr_frame);
+        overlay.draw(&mut curr_frame);
+        if game_over {
+            let result = if game.status() == Status::Won { "you win" } else { "game over" };
+            curr_frame.text_centered(9, &format!(" {} ", result));
+            curr_frame.text_centered(11, &format!(" seed {} ", game.seed));
+        } else if paused {
+            curr_frame.text_centered(9, " paused ");
+            pause_menu.draw(&mut curr_frame);
+        }
+        let (dx, dy) = particles.shake_offset();
+        if (dx, dy) != (0, 0) {
+            curr_frame = curr_frame.shifted(dx, dy);
+        }
+        // the snapshot is the screen as it is, before the notice about it
+        if std::mem::take(&mut take_snapshot) {
+            // to the millisecond, so snapshots taken in quick succession all get kept
+            let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_millis()).unwrap_or(0);
+            let stem = format!("invaders-snapshot-{}", time);
+            let text = match snapshot::save(&curr_frame, screen.theme(), &stem) {
+                Ok(_) => {
+                    info!("snapshot saved to {}.*", stem);
+                    format!(" snapshot saved to {}.* ", stem)
+                }
+                Err(err) => {
+                    warn!("snapshot failed: {}", err);
+                    format!(" snapshot failed: {} ", err)
+                }
+            };
+            notice = Some((text, Timer::from_millis(NOTICE_TIME.as_millis() as u64)));
+        }
+        if let Some((text, _)) = &notice {
+            curr_frame.text_centered(3, text);
+        }
+        // render thread is gone (e.g. it panicked)
+        if screen.render_tx.send(curr_frame).is_err() {
+            break 'gameloop
+        }
         // draw refresh rate
         thread::sleep(Duration::from_millis(2));
 
-        // win?
-        if invaders.all_killed() { 
-            audio.play("win");
+        // the game is decided, wait for the effects to play out
+        if game_over && particles.is_empty() && end_timer.ready {
             break 'gameloop
         }
-        // lose?
-        if invaders.reached_bottom() {
-            audio.play("lose");
-            break 'gameloop
+    }
+    record.finish(&session.stats);
+    recorder.finish().save(REPLAY_FILE)?;
+    // keep an unfinished game, a decided one can't be continued
+    if !game_over {
+        save::save(&game, SAVE_FILE)?;
+        info!("game saved to {} in wave {}", SAVE_FILE, game.wave + 1);
+    } else {
+        info!("game {} with {} points in wave {}", if game.status() == Status::Won { "won" } else { "lost" }, game.score, game.wave + 1);
+        record.scores.add(Score { score: game.score, wave: game.wave + 1, won: game.status() == Status::Won });
+        save::remove(SAVE_FILE)?;
+    }
+    Ok(game)
+}
+
+/// `invaders edit`: a cursor to place invaders and bunkers with, `s` saves the level to `path`
+fn edit(path: PathBuf, mut display: DisplayArgs) -> Result<(), Box<dyn Error>> {
+    // a new file starts out empty
+    let level = match Level::load_or_default(&path) {
+        Ok(level) => level,
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            process::exit(1);
         }
+    };
+    // the speed the level plays at until it sets its own
+    let file = display_config(&mut display);
+    let march_ms = file.config().march_ms;
+    let accessibility = file.accessibility;
 
+    let quit = terminal::quit_signal()?;
+    let screen = Screen::start(&display)?;
 
-    } 
+    let mut editor = Editor::new(level);
+    editor.set_reduced_motion(accessibility.reduced_motion);
+    let mut instant = Instant::now();
+    'edit: while !quit.load(Ordering::SeqCst) {
+        let delta = instant.elapsed();
+        instant = Instant::now();
+
+        // input handling
+        while event::poll(Duration::default())? {
+            if let Event::Key(key_event) = event::read()? {
+                let action = match key_event.code {
+                    KeyCode::Left => Some(EditAction::Move(-1, 0)),
+                    KeyCode::Right => Some(EditAction::Move(1, 0)),
+                    KeyCode::Up => Some(EditAction::Move(0, -1)),
+                    KeyCode::Down => Some(EditAction::Move(0, 1)),
+                    KeyCode::Char('1') => Some(EditAction::Invader(0)),
+                    KeyCode::Char('2') => Some(EditAction::Invader(1)),
+                    KeyCode::Char('b') => Some(EditAction::Bunker),
+                    KeyCode::Char('x') | KeyCode::Delete | KeyCode::Backspace => Some(EditAction::Remove),
+                    KeyCode::Char('+') | KeyCode::Char('=') => Some(EditAction::Faster),
+                    KeyCode::Char('-') => Some(EditAction::Slower),
+                    KeyCode::Char('s') => {
+                        editor.save(&path);
+                        None
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => break 'edit,
+                    _ if is_ctrl_c(&key_event) => break 'edit,
+                    _ => None,
+                };
+                if let Some(action) = action {
+                    editor.apply(action, march_ms);
+                }
+            }
+        }
+        editor.update(delta);
+
+        // draw and render
+        let mut curr_frame = new_frame();
+        editor.draw(&mut curr_frame);
+        if screen.render_tx.send(curr_frame).is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+
+    screen.close()?;
+    if editor.dirty() {
+        eprintln!("{}: quit without saving, changes are lost", path.display());
+    }
+    Ok(())
+}
+
+/// `invaders replay`: plays a recording back at normal speed
+fn watch(replay: Replay, mut display: DisplayArgs) -> Result<(), Box<dyn Error>> {
+    // the viewer's settings, not the player's
+    let accessibility = display_config(&mut display).accessibility;
+
+    let mut sound = Sound::new(!display.no_sound);
+    let quit = terminal::quit_signal()?;
+    let screen = Screen::start(&display)?;
+
+    let mut playback = Playback::new(replay);
+    playback.game.set_accessibility(accessibility);
+    sound.start(&playback.game);
+    let mut particles = Particles::new();
+    particles.set_reduced_motion(accessibility.reduced_motion);
+    let mut instant = Instant::now();
+    let mut lag = Duration::default();
+    'watch: while !quit.load(Ordering::SeqCst) {
+        let delta = instant.elapsed();
+        instant = Instant::now();
+
+        // input handling, the only thing to do is stop watching
+        while event::poll(Duration::default())? {
+            if let Event::Key(key_event) = event::read()? {
+                if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) || is_ctrl_c(&key_event) {
+                    break 'watch;
+                }
+            }
+        }
+        // updates
+        particles.update(delta);
+        lag += delta;
+        while lag >= TICK {
+            lag -= TICK;
+            match playback.step() {
+                Some(events) => events::publish(&events, &mut [&mut sound, &mut particles]),
+                None => break,
+            }
+        }
+
+        // draw and render
+        let mut curr_frame = new_frame();
+        particles.draw(&mut curr_frame);
+        playback.game.draw(&mut curr_frame);
+        curr_frame.text_centered(1, " replay, q to stop ");
+        if screen.render_tx.send(curr_frame).is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(2));
+
+        if playback.done() && particles.is_empty() {
+            break;
+        }
+    }
 
-    // clean up
-    drop(render_tx);
-    render_handle.join().unwrap();
-    audio.wait();
-    stdout.execute(Show)?;
-    stdout.execute(LeaveAlternateScreen)?;
-    terminal::disable_raw_mode()?;
+    screen.close()?;
+    sound.wait();
     Ok(())
 }
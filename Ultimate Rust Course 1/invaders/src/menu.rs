@@ -0,0 +1,58 @@
+use crate::frame::{Drawable, Frame, Pos, Rect};
+use crate::NUM_COLS;
+
+/// items stacked one per row and centred, each can be picked by a click on its text
+pub struct Menu<T> {
+    // row of the first item
+    top: usize,
+    items: Vec<(String, T)>,
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new(top: usize) -> Self {
+        Self { top, items: Vec::new() }
+    }
+
+    pub fn item(mut self, label: &str, value: T) -> Self {
+        self.items.push((format!(" {} ", label), value));
+        self
+    }
+
+    // the cells the item's text covers, as `Frame::text_centered` draws it
+    fn rect(&self, idx: usize) -> Rect {
+        let width = self.items[idx].0.chars().count();
+        Rect::new(Pos::new(NUM_COLS.saturating_sub(width) / 2, self.top + idx), width, 1)
+    }
+
+    /// the item drawn at `pos`, if any
+    pub fn at(&self, pos: Pos) -> Option<T> {
+        (0..self.items.len()).find(|idx| self.rect(*idx).contains(pos)).map(|idx| self.items[idx].1)
+    }
+}
+
+impl<T> Drawable for Menu<T> {
+    fn draw(&self, frame: &mut Frame) {
+        for (idx, (label, _)) in self.items.iter().enumerate() {
+            frame.text_centered(self.top + idx, label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::new_frame;
+
+    #[test]
+    fn test_clicks_hit_the_drawn_text() {
+        let menu = Menu::new(11).item("play", 1).item("quit", 2);
+        let mut frame = new_frame();
+        menu.draw(&mut frame);
+        let x = frame.row(12).position(|glyph| glyph == "q").unwrap();
+        assert_eq!(menu.at(Pos::new(x, 12)), Some(2));
+        // the padding around a label counts, the row next to it doesn't
+        assert_eq!(menu.at(Pos::new(x - 1, 11)), Some(1));
+        assert_eq!(menu.at(Pos::new(x - 2, 11)), None);
+        assert_eq!(menu.at(Pos::new(x, 13)), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// a note of the equal-tempered scale, written like `C4`, `F#2` or `Eb3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Note {
+    // the MIDI number, 69 is A4
+    midi: u8,
+}
+
+const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+impl Note {
+    pub const fn from_midi(midi: u8) -> Self {
+        Self { midi }
+    }
+
+    pub fn hz(self) -> f32 {
+        440.0 * 2f32.powf((self.midi as f32 - 69.0) / 12.0)
+    }
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let octave = self.midi as i32 / 12 - 1;
+        f.pad(&format!("{}{}", NAMES[self.midi as usize % 12], octave))
+    }
+}
+
+impl FromStr for Note {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("unknown note `{}`, expected a letter, maybe # or b, and an octave like F#2", s);
+        let mut chars = s.chars();
+        let letter = chars.next().ok_or_else(err)?;
+        let mut semitone = match letter.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return Err(err()),
+        };
+        let mut rest = chars.as_str();
+        if let Some(stripped) = rest.strip_prefix('#') {
+            semitone += 1;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix('b') {
+            semitone -= 1;
+            rest = stripped;
+        }
+        let octave: i32 = rest.parse().map_err(|_| err())?;
+        let midi = 12 * (octave + 1) + semitone;
+        u8::try_from(midi).ok().filter(|midi| *midi <= 127).map(Note::from_midi).ok_or_else(err)
+    }
+}
+
+// notes are saved by name
+impl Serialize for Note {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Note {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// the four falling bass notes of the arcade march, F#2 F2 E2 D#2
+pub const CLASSIC_MARCH: [Note; 4] = [Note::from_midi(42), Note::from_midi(41), Note::from_midi(40), Note::from_midi(39)];
+
+/// one sequence, used for every wave
+pub fn default_march() -> Vec<Vec<Note>> {
+    vec![CLASSIC_MARCH.to_vec()]
+}
+
+/// which note the army's next step plays: wave `n` loops through sequence
+/// `n` of the list, the list starts over when there are more waves than sequences
+#[derive(Debug, Clone, Default)]
+pub struct Soundtrack {
+    sequences: Vec<Vec<Note>>,
+    wave: usize,
+    step: usize,
+}
+
+impl Soundtrack {
+    pub fn new(sequences: Vec<Vec<Note>>, wave: usize) -> Self {
+        Self { sequences, wave, step: 0 }
+    }
+
+    /// the note for the step the army just took, `None` if there are no notes
+    pub fn next_note(&mut self) -> Option<Note> {
+        if self.sequences.is_empty() {
+            return None;
+        }
+        let sequence = &self.sequences[self.wave % self.sequences.len()];
+        let note = sequence.get(self.step % sequence.len().max(1)).copied();
+        self.step += 1;
+        note
+    }
+
+    /// the next wave starts its sequence from the top
+    pub fn next_wave(&mut self) {
+        self.wave += 1;
+        self.step = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_note_names() {
+        let a4: Note = "A4".parse().unwrap();
+        assert_eq!(a4.hz(), 440.0);
+        assert_eq!("F#2".parse::<Note>().unwrap(), CLASSIC_MARCH[0]);
+        assert_eq!("Eb2".parse::<Note>().unwrap(), CLASSIC_MARCH[3]);
+        assert_eq!(CLASSIC_MARCH[3].to_string(), "D#2");
+        assert!("H2".parse::<Note>().is_err());
+        assert!("C".parse::<Note>().is_err());
+        assert!("C10".parse::<Note>().is_err());
+    }
+
+    #[test]
+    fn test_soundtrack_loops_per_wave() {
+        let low: Vec<Note> = ["C2", "D2"].iter().map(|name| name.parse().unwrap()).collect();
+        let mut soundtrack = Soundtrack::new(vec![CLASSIC_MARCH.to_vec(), low.clone()], 0);
+        let notes: Vec<_> = (0..5).filter_map(|_| soundtrack.next_note()).collect();
+        assert_eq!(notes, [&CLASSIC_MARCH[..], &CLASSIC_MARCH[..1]].concat());
+        soundtrack.next_wave();
+        assert_eq!(soundtrack.next_note(), Some(low[0]));
+        // a third wave goes back to the first sequence
+        soundtrack.next_wave();
+        assert_eq!(soundtrack.next_note(), Some(CLASSIC_MARCH[0]));
+        assert_eq!(Soundtrack::default().next_note(), None);
+    }
+}
@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use rusty_time::timer::Timer;
+
+use crate::events::{GameEvent, Subscriber};
+use crate::frame::{Drawable, Frame, Pos};
+
+/// glyphs a piece of debris fades through over its lifetime
+const DEBRIS: &[&str] = &["✶", "*", "+", "·"];
+/// glyphs a shot trail fades through
+const TRAIL: &[&str] = &["╎", "·"];
+/// directions debris is thrown in, in cells per second
+const SPRAY: &[(f32, f32)] = &[
+    (-12.0, 0.0), (12.0, 0.0), (0.0, -6.0), (0.0, 6.0),
+    (-9.0, -4.5), (9.0, -4.5), (-9.0, 4.5), (9.0, 4.5),
+];
+/// how far the screen is moved on each step of a shake
+const SHAKE: &[(i32, i32)] = &[(1, 0), (-1, 0), (0, 1), (1, -1), (-1, 1), (0, -1)];
+const SHAKE_STEP: Duration = Duration::from_millis(40);
+/// how long the screen shakes when the ship is hit
+const HIT_SHAKE: Duration = Duration::from_millis(600);
+
+struct Particle {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    glyphs: &'static [&'static str],
+    timer: Timer,
+}
+
+impl Particle {
+    fn update(&mut self, delta: Duration) {
+        self.timer.update(delta);
+        self.x += self.vx * delta.as_secs_f32();
+        self.y += self.vy * delta.as_secs_f32();
+    }
+
+    fn dead(&self) -> bool {
+        self.timer.ready
+    }
+
+    /// the glyph for the current age, later glyphs as the particle gets older
+    fn glyph(&self) -> &'static str {
+        let age = 1.0 - self.timer.time_left.as_secs_f32() / self.timer.duration.as_secs_f32();
+        let idx = (age * self.glyphs.len() as f32) as usize;
+        self.glyphs[idx.min(self.glyphs.len() - 1)]
+    }
+}
+
+/// short-lived visual effects, they never affect gameplay
+#[derive(Default)]
+pub struct Particles {
+    particles: Vec<Particle>,
+    shake: Option<Timer>,
+    // reduced motion, the screen never shakes
+    still: bool,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sprays debris outward from `pos`
+    pub fn explode(&mut self, pos: Pos) {
+        for &(vx, vy) in SPRAY {
+            self.particles.push(Particle {
+                x: pos.x as f32 + 0.5,
+                y: pos.y as f32 + 0.5,
+                vx,
+                vy,
+                glyphs: DEBRIS,
+                timer: Timer::from_millis(400),
+            });
+        }
+    }
+
+    /// leaves a fading mark at a cell a shot just left
+    pub fn trail(&mut self, pos: Pos) {
+        self.particles.push(Particle {
+            x: pos.x as f32 + 0.5,
+            y: pos.y as f32 + 0.5,
+            vx: 0.0,
+            vy: 0.0,
+            glyphs: TRAIL,
+            timer: Timer::from_millis(150),
+        });
+    }
+
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        self.still = on;
+        if on {
+            self.shake = None;
+        }
+    }
+
+    pub fn shake(&mut self, duration: Duration) {
+        if self.still {
+            return;
+        }
+        self.shake = Some(Timer::from_millis(duration.as_millis() as u64));
+    }
+
+    /// how far to move the whole frame this tick, (0, 0) when not shaking
+    pub fn shake_offset(&self) -> (i32, i32) {
+        match &self.shake {
+            Some(timer) => {
+                let elapsed = timer.duration.saturating_sub(timer.time_left);
+                let step = (elapsed.as_millis() / SHAKE_STEP.as_millis()) as usize;
+                SHAKE[step % SHAKE.len()]
+            }
+            None => (0, 0),
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        for particle in self.particles.iter_mut() {
+            particle.update(delta);
+        }
+        self.particles.retain(|particle| !particle.dead());
+        if let Some(timer) = self.shake.as_mut() {
+            timer.update(delta);
+            if timer.ready {
+                self.shake = None;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty() && self.shake.is_none()
+    }
+}
+
+impl Subscriber for Particles {
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ShotMoved { from } => self.trail(from),
+            GameEvent::InvaderKilled { pos } | GameEvent::BunkerHit { pos } => self.explode(pos),
+            GameEvent::PlayerHit { pos } => {
+                self.explode(pos);
+                self.shake(HIT_SHAKE);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drawable for Particles {
+    fn draw(&self, frame: &mut Frame) {
+        for particle in self.particles.iter() {
+            frame.draw_at(particle.x.floor() as i32, particle.y.floor() as i32, particle.glyph());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn drawn(particles: &Particles) -> Frame {
+        let mut frame = Frame::new(20, 20);
+        particles.draw(&mut frame);
+        frame
+    }
+
+    #[test]
+    fn test_debris_flies_apart_and_fades() {
+        let mut particles = Particles::new();
+        particles.explode(Pos::new(10, 10));
+        assert_eq!(drawn(&particles).get(Pos::new(10, 10)), Some("✶"));
+        particles.update(Duration::from_millis(100));
+        let frame = drawn(&particles);
+        for pos in [Pos::new(9, 10), Pos::new(11, 10), Pos::new(10, 9), Pos::new(10, 11)] {
+            assert_eq!(frame.get(pos), Some("*"), "{:?}", pos);
+        }
+        assert_eq!(frame.get(Pos::new(10, 10)), Some(" "));
+        particles.update(Duration::from_millis(301));
+        assert!(particles.is_empty());
+    }
+
+    #[test]
+    fn test_trail_fades_in_place() {
+        let mut particles = Particles::new();
+        particles.trail(Pos::new(3, 4));
+        assert_eq!(drawn(&particles).get(Pos::new(3, 4)), Some("╎"));
+        particles.update(Duration::from_millis(100));
+        assert_eq!(drawn(&particles).get(Pos::new(3, 4)), Some("·"));
+        particles.update(Duration::from_millis(60));
+        assert!(particles.is_empty());
+    }
+
+    #[test]
+    fn test_shake_steps_then_stops() {
+        let mut particles = Particles::new();
+        assert_eq!(particles.shake_offset(), (0, 0));
+        particles.shake(Duration::from_millis(100));
+        assert_eq!(particles.shake_offset(), SHAKE[0]);
+        particles.update(SHAKE_STEP);
+        assert_eq!(particles.shake_offset(), SHAKE[1]);
+        particles.update(SHAKE_STEP);
+        assert_eq!(particles.shake_offset(), SHAKE[2]);
+        assert!(!particles.is_empty());
+        particles.update(SHAKE_STEP);
+        assert_eq!(particles.shake_offset(), (0, 0));
+        assert!(particles.is_empty());
+    }
+}
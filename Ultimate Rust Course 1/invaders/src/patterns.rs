@@ -0,0 +1,372 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::frame::{Pos, Rect};
+use crate::invaders::Invader;
+use crate::sprite::Collider;
+use crate::NUM_COLS;
+
+/// which way the army went on a step, descending speeds the army up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Across,
+    Down,
+}
+
+/// how an army moves, one step each time its march timer fires
+///
+/// patterns only move invaders, `Invaders` takes care of timing, speed and hit-testing.
+/// they are `Send` so whole games can run on other threads.
+pub trait Pattern: Send {
+    fn step(&mut self, army: &mut [Invader]) -> Step;
+
+    /// the pattern's progress, for save files. patterns without one can't be saved.
+    fn state(&self) -> Option<PatternState> {
+        None
+    }
+}
+
+/// where a built-in pattern is in its movement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PatternState {
+    March(March),
+    #[serde(rename = "sine")]
+    SineWave(SineWave),
+    Split(Split),
+    ZigZag(ZigZag),
+}
+
+impl PatternState {
+    /// picks the movement up where it was left
+    pub fn build(self) -> Box<dyn Pattern> {
+        match self {
+            PatternState::March(pattern) => Box::new(pattern),
+            PatternState::SineWave(pattern) => Box::new(pattern),
+            PatternState::Split(pattern) => Box::new(pattern),
+            PatternState::ZigZag(pattern) => Box::new(pattern),
+        }
+    }
+}
+
+/// serde helpers for `#[serde(with = "...")]` on a `Box<dyn Pattern>`
+pub(crate) mod boxed {
+    use super::*;
+
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(pattern: &Box<dyn Pattern>, serializer: S) -> Result<S::Ok, S::Error> {
+        match pattern.state() {
+            Some(state) => state.serialize(serializer),
+            None => Err(ser::Error::custom("the army's movement pattern can't be saved")),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<dyn Pattern>, D::Error> {
+        Ok(PatternState::deserialize(deserializer)?.build())
+    }
+}
+
+/// the built-in patterns, so they can be picked by name from a wave or config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    March,
+    SineWave,
+    Split,
+    ZigZag,
+}
+
+impl PatternKind {
+    pub const ALL: [PatternKind; 4] = [
+        PatternKind::March,
+        PatternKind::SineWave,
+        PatternKind::Split,
+        PatternKind::ZigZag,
+    ];
+
+    /// waves cycle through all patterns, starting with the classic march
+    pub fn for_wave(wave: usize) -> Self {
+        Self::ALL[wave % Self::ALL.len()]
+    }
+
+    pub fn build(self) -> Box<dyn Pattern> {
+        match self {
+            PatternKind::March => Box::new(March::new()),
+            PatternKind::SineWave => Box::new(SineWave::new()),
+            PatternKind::Split => Box::new(Split::new()),
+            PatternKind::ZigZag => Box::new(ZigZag::new()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PatternKind::March => "march",
+            PatternKind::SineWave => "sine",
+            PatternKind::Split => "split",
+            PatternKind::ZigZag => "zigzag",
+        }
+    }
+}
+
+impl fmt::Display for PatternKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for PatternKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| format!("unknown movement pattern `{}`", s))
+    }
+}
+
+/// the box around all invaders
+fn bounds<'a>(army: impl IntoIterator<Item = &'a Invader>) -> Option<Rect> {
+    army.into_iter().map(|invader| invader.hitbox()).reduce(|a, b| a.union(&b))
+}
+
+/// whether moving `invader` sideways by `dx` keeps it on screen
+fn fits(invader: &Invader, dx: i32) -> bool {
+    let hitbox = invader.hitbox();
+    hitbox.pos.x as i32 + dx >= 0 && hitbox.right() as i32 + dx <= NUM_COLS as i32
+}
+
+/// moves every invader by `dx`, `dy`, or none of them if one would leave the screen
+/// at the top or left, so the formation never bunches up against a wall
+fn shift<'a>(army: impl IntoIterator<Item = &'a mut Invader>, dx: i32, dy: i32) {
+    let mut army: Vec<&mut Invader> = army.into_iter().collect();
+    let moved: Option<Vec<Pos>> = army.iter().map(|invader| invader.pos.offset(dx, dy)).collect();
+    if let Some(moved) = moved {
+        for (invader, pos) in army.iter_mut().zip(moved) {
+            invader.pos = pos;
+        }
+    }
+}
+
+/// side to side, one row down at each edge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct March {
+    direction: i32,
+}
+
+impl March {
+    pub fn new() -> Self {
+        Self { direction: 1 }
+    }
+}
+
+impl Default for March {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pattern for March {
+    fn step(&mut self, army: &mut [Invader]) -> Step {
+        if army.iter().all(|invader| fits(invader, self.direction)) {
+            shift(army.iter_mut(), self.direction, 0);
+            Step::Across
+        } else {
+            self.direction = -self.direction;
+            shift(army.iter_mut(), 0, 1);
+            Step::Down
+        }
+    }
+
+    fn state(&self) -> Option<PatternState> {
+        Some(PatternState::March(self.clone()))
+    }
+}
+
+/// every row sways on its own sine curve, the army drops a row every period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SineWave {
+    t: u32,
+}
+
+impl SineWave {
+    const AMPLITUDE: f32 = 2.0;
+    const SPEED: f32 = 0.5;
+    // phase difference between rows
+    const ROW_PHASE: f32 = 0.8;
+    const PERIOD: u32 = 12;
+
+    pub fn new() -> Self {
+        Self { t: 0 }
+    }
+
+    fn offset(t: u32, y: usize) -> i32 {
+        (Self::AMPLITUDE * (t as f32 * Self::SPEED + y as f32 * Self::ROW_PHASE).sin()).round() as i32
+    }
+}
+
+impl Default for SineWave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pattern for SineWave {
+    fn step(&mut self, army: &mut [Invader]) -> Step {
+        let t = self.t;
+        self.t += 1;
+        let dx = |invader: &Invader| Self::offset(t + 1, invader.pos.y) - Self::offset(t, invader.pos.y);
+        // drop a row once per period, or early if a row would leave the screen
+        if self.t.is_multiple_of(Self::PERIOD) || !army.iter().all(|invader| fits(invader, dx(invader))) {
+            shift(army.iter_mut(), 0, 1);
+            return Step::Down;
+        }
+        for invader in army.iter_mut() {
+            let dx = dx(invader);
+            shift(Some(invader), dx, 0);
+        }
+        Step::Across
+    }
+
+    fn state(&self) -> Option<PatternState> {
+        Some(PatternState::SineWave(self.clone()))
+    }
+}
+
+/// the army parts in the middle, both halves move out to the edges and back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    center: Option<usize>,
+    outward: bool,
+}
+
+impl Split {
+    pub fn new() -> Self {
+        Self { center: None, outward: true }
+    }
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pattern for Split {
+    fn step(&mut self, army: &mut [Invader]) -> Step {
+        let center = *self.center.get_or_insert_with(|| {
+            bounds(army.iter()).map(|rect| rect.center().x).unwrap_or(NUM_COLS / 2)
+        });
+        let left = |invader: &Invader| invader.hitbox().center().x < center;
+        let dir = if self.outward { 1 } else { -1 };
+        let turn = if self.outward {
+            // the outer edge of either half reached a wall
+            !army.iter().all(|invader| fits(invader, if left(invader) { -dir } else { dir }))
+        } else {
+            // the inner edge of either half reached the middle
+            army.iter().any(|invader| {
+                let hitbox = invader.hitbox();
+                if left(invader) { hitbox.right() >= center } else { hitbox.pos.x <= center + 1 }
+            })
+        };
+        if turn {
+            self.outward = !self.outward;
+            shift(army.iter_mut(), 0, 1);
+            return Step::Down;
+        }
+        for invader in army.iter_mut() {
+            let dx = if left(invader) { -dir } else { dir };
+            shift(Some(invader), dx, 0);
+        }
+        Step::Across
+    }
+
+    fn state(&self) -> Option<PatternState> {
+        Some(PatternState::Split(self.clone()))
+    }
+}
+
+/// diagonal steps, bouncing off the walls and dropping a row every few steps
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZigZag {
+    direction: i32,
+    t: u32,
+}
+
+impl ZigZag {
+    const DROP_EVERY: u32 = 3;
+
+    pub fn new() -> Self {
+        Self { direction: 1, t: 0 }
+    }
+}
+
+impl Default for ZigZag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pattern for ZigZag {
+    fn step(&mut self, army: &mut [Invader]) -> Step {
+        self.t += 1;
+        if !army.iter().all(|invader| fits(invader, self.direction)) {
+            self.direction = -self.direction;
+        }
+        let dx = if army.iter().all(|invader| fits(invader, self.direction)) { self.direction } else { 0 };
+        if self.t.is_multiple_of(Self::DROP_EVERY) {
+            shift(army.iter_mut(), dx, 1);
+            Step::Down
+        } else {
+            shift(army.iter_mut(), dx, 0);
+            Step::Across
+        }
+    }
+
+    fn state(&self) -> Option<PatternState> {
+        Some(PatternState::ZigZag(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::invaders::Invaders;
+    use crate::sprite;
+    use crate::NUM_ROWS;
+
+    #[test]
+    fn test_patterns_stay_on_screen() {
+        for kind in PatternKind::ALL {
+            for sprite in [sprite::INVADER, sprite::INVADER_LARGE] {
+                let mut army = Invaders::with_sprite(sprite).army().iter()
+                    .map(|invader| Invader::new(invader.pos, invader.sprite))
+                    .collect::<Vec<_>>();
+                let mut pattern = kind.build();
+                let mut downs = 0;
+                // stop before the army could reach the bottom
+                while downs < 10 {
+                    if pattern.step(&mut army) == Step::Down {
+                        downs += 1;
+                    }
+                    let rect = bounds(army.iter()).unwrap();
+                    assert!(rect.right() <= NUM_COLS && rect.bottom() <= NUM_ROWS, "{} left the screen", kind);
+                    // an invader held back at the left wall would end up inside another
+                    for (idx, invader) in army.iter().enumerate() {
+                        assert!(!army[..idx].iter().any(|other| other.collides_with(invader)), "{} bunched up at {:?}", kind, invader.pos);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pattern_names() {
+        for kind in PatternKind::ALL {
+            assert_eq!(kind.name().parse::<PatternKind>(), Ok(kind));
+        }
+        assert!("wobble".parse::<PatternKind>().is_err());
+    }
+}
@@ -1,65 +1,109 @@
 use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::bunkers::Bunkers;
+use crate::config::Config;
 use crate::invaders::Invaders;
-use crate::frame::{Drawable, Frame};
+use crate::frame::{Drawable, Frame, Pos, Rect};
 use crate::{NUM_COLS, NUM_ROWS};
 use crate::shot::Shot;
+use crate::sprite::{self, Collider, Sprite};
 
+#[derive(Serialize, Deserialize)]
 pub struct Player {
-    x: usize,
-    y: usize,
-    shots: Vec<Shot>
+    pos: Pos,
+    sprite: Sprite,
+    shots: Vec<Shot>,
+    max_shots: usize,
+    shot_step: Duration,
 }
 
 impl Player {
     pub fn new () -> Self {
+        Self::with_sprite(sprite::PLAYER)
+    }
+
+    pub fn with_sprite(sprite: Sprite) -> Self {
         Self {
-        x: NUM_COLS / 2, // center horizontal
-        y: NUM_ROWS -1, // last row, bottom
+        // center horizontal, bottom rows
+        pos: Pos::new((NUM_COLS - sprite.width()) / 2, NUM_ROWS - sprite.height()),
+        sprite,
         shots: Vec::new(),
+        max_shots: Config::default().max_shots,
+        shot_step: Config::default().shot_step(),
         }
     }
 
+    /// applies the shot limit and speed, shots already fired keep their speed
+    pub fn set_config(&mut self, config: &Config) {
+        self.max_shots = config.max_shots;
+        self.shot_step = config.shot_step();
+    }
+
+    pub fn shots(&self) -> &[Shot] {
+        &self.shots
+    }
+
     pub fn move_left(&mut self) {
-        if self.x > 0 {
-            self.x -= 1;
+        if self.pos.x > 0 {
+            self.pos.x -= 1;
         }
     }
 
     pub fn move_right(&mut self) {
-        if self.x < NUM_COLS -1 {
-            self.x += 1;
+        if self.hitbox().right() < NUM_COLS {
+            self.pos.x += 1;
         }
     }
 
     pub fn shoot(&mut self) -> bool {
-        // no more than 2 shots on screen
-        if self.shots.len() < 2 {
-            // shot should start directly above player
-            self.shots.push(Shot::new(self.x, self.y- 1));
-            true
-        } else {
-            false
+        // no more than `max_shots` shots on screen
+        if self.shots.len() < self.max_shots {
+            // shot should start directly above the middle of the ship
+            if let Some(pos) = self.pos.offset((self.sprite.width() / 2) as i32, -1) {
+                self.shots.push(Shot::new(pos, self.shot_step));
+                return true;
+            }
         }
+        false
     }
 
-    pub fn update(&mut self, delta: Duration) {
+    /// returns the cells the shots moved away from, for drawing trails
+    pub fn update(&mut self, delta: Duration) -> Vec<Pos> {
+        // shots that died last update were still hit-tested, drop them now
+        self.shots.retain(|shot| !shot.dead());
+        self.shots.iter_mut().flat_map(|shot| shot.update(delta)).collect()
+    }
+
+    /// returns where shots hit an invader, checking the whole way each shot
+    /// travelled since the last update
+    pub fn detect_hits(&mut self, invaders: &mut Invaders) -> Vec<Pos> {
+        let mut hits = Vec::new();
         for shot in self.shots.iter_mut() {
-            shot.update(delta);
+            if shot.exploding {
+                continue;
+            }
+            if let Some(pos) = invaders.kill_invader_swept(shot.path()) {
+                hits.push(pos);
+                shot.explode(pos);
+            }
         }
-        self.shots.retain(|shot| !shot.dead());
+        hits
     }
 
-    pub fn detect_hits(&mut self, invaders: &mut Invaders) -> bool {
-        let mut hit_something = false;
+    /// returns where shots ran into a bunker, those shots explode there.
+    /// check before `detect_hits`, the army is above the bunkers.
+    pub fn detect_blocks(&mut self, bunkers: &mut Bunkers) -> Vec<Pos> {
+        let mut blocks = Vec::new();
         for shot in self.shots.iter_mut() {
-            if !shot.exploding {
-                if invaders.kill_invader_at(shot.x, shot.y) {
-                    hit_something = true;
-                    shot.explode();
-                }
+            if shot.exploding {
+                continue;
+            }
+            if let Some(pos) = bunkers.hit_swept(shot.path()) {
+                blocks.push(pos);
+                shot.explode(pos);
             }
         }
-        hit_something
+        blocks
     }
 }
 
@@ -70,10 +114,16 @@ impl Default for Player {
 }
 
 
+impl Collider for Player {
+    fn hitbox(&self) -> Rect {
+        self.sprite.hitbox(self.pos)
+    }
+}
+
 impl Drawable for Player {
     fn draw(&self, frame: &mut Frame) {
         // draw player
-        frame[self.x][self.y] = "⟁";
+        self.sprite.draw(frame, self.pos, 0);
         // draw shots
         for shot in self.shots.iter() {
             shot.draw(frame);
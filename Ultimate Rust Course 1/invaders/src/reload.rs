@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use rusty_time::timer::Timer;
+
+use crate::frame::{Drawable, Frame, Pos};
+use crate::NUM_COLS;
+
+/// how often watched files are looked at
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// row the banner is drawn on, just below the score line
+pub const BANNER_ROW: usize = 1;
+
+/// files the game follows while it runs, `K` tells them apart. a file counts as
+/// changed when its modification time does, or when it appears or goes away.
+pub struct Watcher<K> {
+    files: Vec<(K, PathBuf, Option<SystemTime>)>,
+    timer: Timer,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl<K: Copy> Watcher<K> {
+    pub fn new() -> Self {
+        Self { files: Vec::new(), timer: Timer::from_millis(POLL_INTERVAL.as_millis() as u64) }
+    }
+
+    /// follows `path` from here on, the file as it is now doesn't count as a change
+    pub fn watch(mut self, key: K, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = modified(&path);
+        self.files.push((key, path, modified));
+        self
+    }
+
+    /// the files that changed since they were last looked at, every `POLL_INTERVAL`
+    pub fn update(&mut self, delta: Duration) -> Vec<K> {
+        self.timer.update(delta);
+        if !self.timer.ready {
+            return Vec::new();
+        }
+        self.timer.reset();
+        self.changed()
+    }
+
+    /// the files that changed since they were last looked at, right now
+    pub fn changed(&mut self) -> Vec<K> {
+        let mut changed = Vec::new();
+        for (key, path, last) in self.files.iter_mut() {
+            let now = modified(path);
+            if now != *last {
+                *last = now;
+                changed.push(*key);
+            }
+        }
+        changed
+    }
+}
+
+impl<K: Copy> Default for Watcher<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// why watched files didn't load, drawn across the top until each one is fixed
+pub struct Banner<K> {
+    errors: Vec<(K, String)>,
+}
+
+impl<K: PartialEq> Banner<K> {
+    pub fn new() -> Self {
+        Self { errors: Vec::new() }
+    }
+
+    /// records how loading `key` went, `None` clears its error
+    pub fn set(&mut self, key: K, error: Option<String>) {
+        self.errors.retain(|(other, _)| *other != key);
+        if let Some(error) = error {
+            self.errors.push((key, error));
+        }
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        self.errors.iter().map(|(_, error)| error.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl<K: PartialEq> Default for Banner<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Drawable for Banner<K> {
+    fn draw(&self, frame: &mut Frame) {
+        // the latest error, its first line is usually the one that says what's wrong
+        if let Some((_, error)) = self.errors.last() {
+            let line = error.lines().next().unwrap_or_default();
+            let text: String = format!(" ! {}", line).chars().take(NUM_COLS).collect();
+            // padded across the whole row so it reads as a bar
+            frame.text(Pos::new(0, BANNER_ROW), &format!("{:<1$}", text, NUM_COLS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::new_frame;
+    use std::fs::File;
+
+    #[test]
+    fn test_watcher_sees_edits() {
+        let path = std::env::temp_dir().join(format!("invaders-watch-{}.toml", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = Watcher::new().watch(1, &path);
+        assert!(watcher.changed().is_empty());
+        fs::write(&path, "max_shots = 3").unwrap();
+        assert_eq!(watcher.changed(), [1]);
+        assert!(watcher.changed().is_empty());
+        // the clock may not have moved on, set the time the way an editor would
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(watcher.update(POLL_INTERVAL / 2).is_empty());
+        assert_eq!(watcher.update(POLL_INTERVAL), [1]);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.changed(), [1]);
+    }
+
+    #[test]
+    fn test_banner_shows_until_fixed() {
+        let mut banner = Banner::new();
+        banner.set("config", Some("invaders.toml: could not parse config\nline 2".to_string()));
+        let mut frame = new_frame();
+        banner.draw(&mut frame);
+        let row: String = frame.row(BANNER_ROW).collect();
+        assert!(row.starts_with(" ! invaders.toml: could not parse config"), "{}", row);
+        banner.set("config", None);
+        assert!(banner.is_empty());
+    }
+}
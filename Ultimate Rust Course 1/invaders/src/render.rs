@@ -1,26 +1,87 @@
-use std::io::{Stdout, Write};
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
 use crossterm::cursor::MoveTo;
 use crossterm::terminal::{Clear, ClearType};
 use crossterm::QueueableCommand;
-use crossterm::style::{SetBackgroundColor, Color};
+use crossterm::style::{SetBackgroundColor, SetForegroundColor, Color};
+use serde::{Deserialize, Serialize};
 
 use crate::frame::Frame;
 
-pub fn render(stdout: &mut Stdout, last_frame: &Frame, curr_frame: &Frame, force: bool) {
+/// colours the frame is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// white on black, framed in blue
+    #[default]
+    Classic,
+    /// the terminal's own colours
+    Mono,
+    /// green on black, framed in magenta
+    Neon,
+    /// yellow on black, framed in white, for low vision
+    Contrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::Classic, Theme::Mono, Theme::Neon, Theme::Contrast];
+
+    /// colours of the screen around the frame, the frame and the glyphs
+    pub(crate) fn colors(self) -> (Color, Color, Color) {
+        match self {
+            Theme::Classic => (Color::Blue, Color::Black, Color::White),
+            Theme::Mono => (Color::Reset, Color::Reset, Color::Reset),
+            Theme::Neon => (Color::Magenta, Color::Black, Color::Green),
+            Theme::Contrast => (Color::White, Color::Black, Color::Yellow),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "classic",
+            Theme::Mono => "mono",
+            Theme::Neon => "neon",
+            Theme::Contrast => "contrast",
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|theme| theme.name() == s)
+            .ok_or_else(|| format!("unknown theme `{}`, expected classic, mono, neon or contrast", s))
+    }
+}
+
+/// draws the cells that changed since `last_frame` to `out`, usually stdout,
+/// or all of them if `force`. `out` is flushed once at the end.
+pub fn render<W: Write>(out: &mut W, last_frame: &Frame, curr_frame: &Frame, force: bool, theme: Theme) {
     if force {
-        stdout.queue(SetBackgroundColor(Color::Blue)).unwrap();
-        stdout.queue(Clear(ClearType::All)).unwrap();
-        stdout.queue(SetBackgroundColor(Color::Black)).unwrap();
-    } 
-
-    for (x, col) in curr_frame.iter().enumerate() {
-        for (y, s) in col.iter().enumerate() {
-            if *s != last_frame[x][y] || force {
-                stdout.queue(MoveTo(x as u16, y as u16)).unwrap();
-                print!("{}", s);    
-            }
+        let (border, background, foreground) = theme.colors();
+        out.queue(SetBackgroundColor(border)).unwrap();
+        out.queue(Clear(ClearType::All)).unwrap();
+        out.queue(SetBackgroundColor(background)).unwrap();
+        out.queue(SetForegroundColor(foreground)).unwrap();
+    }
+
+    for (pos, s) in curr_frame.iter() {
+        if force || last_frame.get(pos) != Some(s) {
+            out.queue(MoveTo(pos.x as u16, pos.y as u16)).unwrap();
+            write!(out, "{}", s).unwrap();
         }
     }
-    stdout.flush().unwrap();
+    out.flush().unwrap();
 
-}
\ No newline at end of file
+}
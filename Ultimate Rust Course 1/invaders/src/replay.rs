@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::game::{Action, Game, Reload, TICK};
+use crate::save::{self, SaveError, VERSION};
+
+/// the last game played is always kept here, in the working directory
+pub const REPLAY_FILE: &str = "invaders.replay";
+
+/// an action and the tick it was taken before
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input(pub u64, pub Action);
+
+/// a file edited during the game and the tick it was picked up before
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Reloaded(pub u64, pub Reload);
+
+/// a recorded game: where it started and every action the player took.
+/// the game is deterministic, so that is enough to play it again tick for tick.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    // shares the save format for `start`, so it shares its version too
+    version: u32,
+    /// how many ticks the game ran for
+    pub ticks: u64,
+    pub inputs: Vec<Input>,
+    /// files edited while the game ran, they are part of how it went
+    #[serde(default)]
+    pub reloads: Vec<Reloaded>,
+    pub start: Game,
+}
+
+impl Replay {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let text = toml::to_string(self).map_err(|err| SaveError::Parse(err.to_string()))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let text = fs::read_to_string(path)?;
+        save::check_version(&text)?;
+        toml::from_str(&text).map_err(|err| SaveError::Parse(err.to_string()))
+    }
+}
+
+/// writes down what happens to a game, call `record` for every action that
+/// did something and `tick` for every `Game::update(TICK)`
+pub struct Recorder {
+    replay: Replay,
+}
+
+impl Recorder {
+    /// starts from a copy of `game` as it is now
+    pub fn new(game: &Game) -> Result<Self, SaveError> {
+        let start = save::from_str(&save::to_string(game)?)?;
+        Ok(Self { replay: Replay { version: VERSION, ticks: 0, inputs: Vec::new(), reloads: Vec::new(), start } })
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.replay.inputs.push(Input(self.replay.ticks, action));
+    }
+
+    /// call with what is handed to `Game::reload`
+    pub fn reload(&mut self, reload: Reload) {
+        self.replay.reloads.push(Reloaded(self.replay.ticks, reload));
+    }
+
+    pub fn tick(&mut self) {
+        self.replay.ticks += 1;
+    }
+
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// plays a replay back one tick at a time
+pub struct Playback {
+    pub game: Game,
+    inputs: Vec<Input>,
+    next: usize,
+    reloads: Vec<Reloaded>,
+    next_reload: usize,
+    tick: u64,
+    ticks: u64,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            game: replay.start,
+            inputs: replay.inputs,
+            next: 0,
+            reloads: replay.reloads,
+            next_reload: 0,
+            tick: 0,
+            ticks: replay.ticks,
+        }
+    }
+
+    /// applies the tick's reloads and actions and advances the game by `TICK`,
+    /// `None` once the recording is over
+    pub fn step(&mut self) -> Option<Vec<GameEvent>> {
+        if self.done() {
+            return None;
+        }
+        while let Some(Reloaded(tick, reload)) = self.reloads.get(self.next_reload) {
+            if *tick > self.tick {
+                break;
+            }
+            self.game.reload(reload.clone());
+            self.next_reload += 1;
+        }
+        while let Some(&Input(tick, action)) = self.inputs.get(self.next) {
+            if tick > self.tick {
+                break;
+            }
+            self.game.act(action);
+            self.next += 1;
+        }
+        self.tick += 1;
+        Some(self.game.update(TICK))
+    }
+
+    pub fn done(&self) -> bool {
+        self.tick >= self.ticks
+    }
+}
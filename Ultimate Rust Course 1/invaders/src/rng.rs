@@ -0,0 +1,84 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// the game's one source of randomness, owned by `Game` and handed to whatever needs it
+///
+/// a splitmix64 generator rather than a crate's: replays and saves store its state,
+/// so the sequence for a seed must never change between builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    #[serde(with = "as_i64")]
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// a number in `0..n`, `n` must be above 0
+    pub fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// true with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+/// a seed for when the player didn't pick one
+pub fn seed_from_time() -> u64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos()).unwrap_or(0);
+    // one round of the generator spreads the clock's low-entropy bits
+    Rng::new(nanos as u64).next_u64()
+}
+
+/// saves a `u64` as the `i64` with the same bits, TOML has no integers above `i64::MAX`
+pub mod as_i64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(*value as i64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        i64::deserialize(deserializer).map(|value| value as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{config::Config, game::Game};
+
+    #[test]
+    fn test_sequence_is_fixed() {
+        // reference output of splitmix64 for seed 0
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        assert!((0..100).all(|_| a.below(7) == b.below(7)));
+        assert!((0..100).all(|_| a.below(7) < 7));
+    }
+
+    #[test]
+    fn test_seed_changes_the_game() {
+        let first_step = |seed| Game::with_seed(Config::default(), seed).invaders.time_to_step();
+        assert_eq!(first_step(3), first_step(3));
+        let steps: HashSet<_> = (0..20).map(first_step).collect();
+        assert!(steps.len() > 1, "every seed started the army after {:?}", steps);
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Game;
+
+/// where a game quit half-way is kept, in the working directory
+pub const SAVE_FILE: &str = "invaders.save";
+
+/// bumped whenever the layout of the saved state changes
+pub const VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    game: Game,
+}
+
+// read on its own first, so an old file gives a version error rather than a parse error
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// the whole game state as TOML
+pub fn to_string(game: &Game) -> Result<String, SaveError> {
+    toml::to_string(&SaveRef { version: VERSION, game }).map_err(|err| SaveError::Parse(err.to_string()))
+}
+
+/// a game exactly as it was saved by `to_string`
+pub fn from_str(s: &str) -> Result<Game, SaveError> {
+    check_version(s)?;
+    let file: SaveFile = toml::from_str(s).map_err(|err| SaveError::Parse(err.to_string()))?;
+    Ok(file.game)
+}
+
+/// fails unless the file was written by this version of the game
+pub(crate) fn check_version(s: &str) -> Result<(), SaveError> {
+    let header: Header = toml::from_str(s).map_err(|err| SaveError::Parse(err.to_string()))?;
+    if header.version != VERSION {
+        return Err(SaveError::Version(header.version));
+    }
+    Ok(())
+}
+
+pub fn save(game: &Game, path: impl AsRef<Path>) -> Result<(), SaveError> {
+    fs::write(path, to_string(game)?)?;
+    Ok(())
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Game, SaveError> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+/// like `load`, but a missing file just means there is nothing to continue
+pub fn load_if_present(path: impl AsRef<Path>) -> Result<Option<Game>, SaveError> {
+    match load(path) {
+        Ok(game) => Ok(Some(game)),
+        Err(SaveError::Io(err)) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// throws a save away once its game is decided, a missing file is fine
+pub fn remove(path: impl AsRef<Path>) -> Result<(), SaveError> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Parse(String),
+    /// the file was written by another version of the game
+    Version(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "{}", err),
+            SaveError::Parse(msg) => write!(f, "could not parse: {}", msg),
+            SaveError::Version(version) => write!(f, "written in format version {}, expected {}", version, VERSION),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+/// serde helpers for `#[serde(with = "...")]` on a `Timer`, which doesn't implement serde itself
+pub(crate) mod timer {
+    use std::time::Duration;
+
+    use rusty_time::timer::Timer;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct TimerState {
+        duration: Duration,
+        time_left: Duration,
+        ready: bool,
+    }
+
+    pub fn serialize<S: Serializer>(timer: &Timer, serializer: S) -> Result<S::Ok, S::Error> {
+        TimerState { duration: timer.duration, time_left: timer.time_left, ready: timer.ready }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timer, D::Error> {
+        let TimerState { duration, time_left, ready } = TimerState::deserialize(deserializer)?;
+        Ok(Timer { duration, time_left, ready })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_other_versions_are_rejected() {
+        let text = to_string(&Game::new()).unwrap().replace(&format!("version = {}", VERSION), "version = 99");
+        assert!(matches!(from_str(&text), Err(SaveError::Version(99))));
+        assert!(matches!(from_str(&format!("version = {}", VERSION)), Err(SaveError::Parse(_))));
+    }
+}
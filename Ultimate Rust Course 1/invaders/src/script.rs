@@ -0,0 +1,402 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::packages::{BasicArrayPackage, BasicMapPackage, BasicMathPackage, CorePackage, LogicPackage, MoreStringPackage, Package};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, ParseError, Scope, AST};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::frame::Pos;
+use crate::level::{Level, KINDS};
+use crate::patterns::PatternKind;
+use crate::sprite::{self, Sprite};
+
+// work a single hook may do before it is stopped, so a runaway loop can't hang the game
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// the functions the game calls in a wave script, those the script doesn't define are skipped
+///
+/// ```rhai
+/// fn on_wave(game) {
+///     clear();
+///     for x in range(2, 38, 4) { place(x, 2, "invader_large"); }
+///     pattern(if game.wave % 2 == 0 { "zigzag" } else { "sine" });
+/// }
+///
+/// fn on_invader_killed(game, x, y) {
+///     if game.invaders < 3 { march_ms(300); }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// `on_wave(game)`, once the wave's army is in place
+    Wave,
+    /// `on_invader_killed(game, x, y)`, after a shot took out the invader at x, y
+    InvaderKilled(Pos),
+    /// `on_army_moved(game)`, after every step of the army
+    ArmyMoved,
+}
+
+impl Hook {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::Wave => "on_wave",
+            Hook::InvaderKilled(_) => "on_invader_killed",
+            Hook::ArmyMoved => "on_army_moved",
+        }
+    }
+
+    // the arguments after `game`
+    fn args(self) -> Vec<Dynamic> {
+        match self {
+            Hook::InvaderKilled(pos) => vec![Dynamic::from(pos.x as i64), Dynamic::from(pos.y as i64)],
+            Hook::Wave | Hook::ArmyMoved => Vec::new(),
+        }
+    }
+}
+
+/// what a script asked for, done by the game once the hook returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// `place(x, y)` or `place(x, y, kind)`, skipped where it would overlap an invader
+    Place(Pos, Sprite),
+    /// `clear()`, removes the whole army
+    Clear,
+    /// `pattern(name)`, how the army moves from its next step on
+    Pattern(PatternKind),
+    /// `march_ms(ms)`, time between steps until the army next speeds up
+    MarchMs(u64),
+}
+
+/// the game as scripts see it, the `game` argument of every hook. scripts can't change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct State {
+    /// counting from 0
+    pub wave: usize,
+    pub waves: usize,
+    pub score: u32,
+    /// invaders left in the army
+    pub invaders: usize,
+    /// the column of the middle of the ship
+    pub player_x: usize,
+}
+
+impl State {
+    fn to_map(self) -> Map {
+        let mut map = Map::new();
+        map.insert("wave".into(), Dynamic::from(self.wave as i64));
+        map.insert("waves".into(), Dynamic::from(self.waves as i64));
+        map.insert("score".into(), Dynamic::from(self.score as i64));
+        map.insert("invaders".into(), Dynamic::from(self.invaders as i64));
+        map.insert("player_x".into(), Dynamic::from(self.player_x as i64));
+        map
+    }
+}
+
+/// a compiled wave script
+///
+/// scripts run sandboxed: no file or module access, no clock or randomness, so games
+/// stay repeatable, and limits on how much a hook may do. the first error stops
+/// the script for the rest of the game.
+pub struct Script {
+    // kept to save the script with the game, it is compiled again on load
+    source: String,
+    engine: Engine,
+    ast: AST,
+    // filled by the API functions while a hook runs
+    commands: Arc<Mutex<Vec<Command>>>,
+    error: Option<ScriptError>,
+}
+
+impl Script {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// what stopped the script, if anything did
+    pub fn error(&self) -> Option<&ScriptError> {
+        self.error.as_ref()
+    }
+
+    fn defines(&self, name: &str, arity: usize) -> bool {
+        self.ast.iter_functions().any(|function| function.name == name && function.params.len() == arity)
+    }
+
+    /// calls `hook` and returns what it asked for. a failing hook asks for nothing,
+    /// its error is kept and no hook runs after it.
+    pub fn run(&mut self, hook: Hook, state: State) -> Vec<Command> {
+        let mut args = vec![Dynamic::from_map(state.to_map())];
+        args.extend(hook.args());
+        if self.error.is_some() || !self.defines(hook.name(), args.len()) {
+            return Vec::new();
+        }
+        // only the functions run, never code outside them
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, hook.name(), args);
+        let commands = std::mem::take(&mut *self.commands.lock().unwrap());
+        match result {
+            Ok(_) => commands,
+            Err(err) => {
+                self.error = Some(ScriptError::runtime(hook, *err));
+                Vec::new()
+            }
+        }
+    }
+}
+
+// the copy is compiled from the source again, it starts without an error
+// a stopped script stays stopped in its copy, which is how replays get it
+impl Clone for Script {
+    fn clone(&self) -> Self {
+        let mut script: Script = self.source.parse().expect("the source compiled before");
+        script.error = match &self.error {
+            Some(ScriptError::Runtime { hook, line, message }) => Some(ScriptError::Runtime { hook, line: *line, message: message.clone() }),
+            _ => None,
+        };
+        script
+    }
+}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let engine = engine(&commands);
+        let ast = engine.compile(s)?;
+        Ok(Self { source: s.to_string(), engine, ast, commands, error: None })
+    }
+}
+
+// scripts are saved as their source, plus the error that stopped them so they stay stopped
+#[derive(Serialize, Deserialize)]
+struct SavedScript {
+    source: String,
+    error: Option<SavedError>,
+}
+
+// only runtime errors can stop a script that compiled
+#[derive(Serialize, Deserialize)]
+struct SavedError {
+    hook: String,
+    line: Option<usize>,
+    message: String,
+}
+
+impl Serialize for Script {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let error = match &self.error {
+            Some(ScriptError::Runtime { hook, line, message }) => Some(SavedError { hook: hook.to_string(), line: *line, message: message.clone() }),
+            _ => None,
+        };
+        SavedScript { source: self.source.clone(), error }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Script {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedScript::deserialize(deserializer)?;
+        let mut script: Script = saved.source.parse().map_err(de::Error::custom)?;
+        if let Some(SavedError { hook, line, message }) = saved.error {
+            let hooks = [Hook::Wave, Hook::InvaderKilled(Pos::new(0, 0)), Hook::ArmyMoved];
+            let hook = hooks
+                .map(Hook::name)
+                .into_iter()
+                .find(|name| *name == hook)
+                .ok_or_else(|| de::Error::custom(format!("`{}` is not a hook", hook)))?;
+            script.error = Some(ScriptError::Runtime { hook, line, message });
+        }
+        Ok(script)
+    }
+}
+
+// only the language itself plus the game's API, see `Script`
+fn engine(commands: &Arc<Mutex<Vec<Command>>>) -> Engine {
+    let mut engine = Engine::new_raw();
+    CorePackage::new().register_into_engine(&mut engine);
+    LogicPackage::new().register_into_engine(&mut engine);
+    BasicMathPackage::new().register_into_engine(&mut engine);
+    BasicArrayPackage::new().register_into_engine(&mut engine);
+    BasicMapPackage::new().register_into_engine(&mut engine);
+    MoreStringPackage::new().register_into_engine(&mut engine);
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1024)
+        .set_max_array_size(1024)
+        .set_max_map_size(256)
+        .disable_symbol("eval");
+
+    let queue = commands.clone();
+    engine.register_fn("place", move |x: i64, y: i64| -> Result<(), Box<EvalAltResult>> {
+        queue.lock().unwrap().push(place(x, y, sprite::INVADER)?);
+        Ok(())
+    });
+    let queue = commands.clone();
+    engine.register_fn("place", move |x: i64, y: i64, kind: &str| -> Result<(), Box<EvalAltResult>> {
+        let sprite = sprite::by_name(kind)
+            .filter(|sprite| KINDS.contains(sprite))
+            .ok_or_else(|| format!("`{}` is not an invader, expected invader or invader_large", kind))?;
+        queue.lock().unwrap().push(place(x, y, sprite)?);
+        Ok(())
+    });
+    let queue = commands.clone();
+    engine.register_fn("clear", move || queue.lock().unwrap().push(Command::Clear));
+    let queue = commands.clone();
+    engine.register_fn("pattern", move |name: &str| -> Result<(), Box<EvalAltResult>> {
+        queue.lock().unwrap().push(Command::Pattern(name.parse::<PatternKind>()?));
+        Ok(())
+    });
+    let queue = commands.clone();
+    engine.register_fn("march_ms", move |ms: i64| -> Result<(), Box<EvalAltResult>> {
+        if ms <= 0 {
+            return Err(format!("march_ms must be above 0, got {}", ms).into());
+        }
+        queue.lock().unwrap().push(Command::MarchMs(ms as u64));
+        Ok(())
+    });
+    engine
+}
+
+fn place(x: i64, y: i64, sprite: Sprite) -> Result<Command, String> {
+    let pos = usize::try_from(x).ok().zip(usize::try_from(y).ok()).map(|(x, y)| Pos::new(x, y));
+    match pos {
+        Some(pos) if Level::fits(sprite.hitbox(pos)) => Ok(Command::Place(pos, sprite)),
+        _ => Err(format!("an invader at {},{} is off the field", x, y)),
+    }
+}
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Io(io::Error),
+    /// the script doesn't compile
+    Parse { line: Option<usize>, message: String },
+    /// a hook failed while the game ran
+    Runtime { hook: &'static str, line: Option<usize>, message: String },
+}
+
+impl ScriptError {
+    fn runtime(hook: Hook, mut err: EvalAltResult) -> Self {
+        // errors inside functions the hook called are wrapped, the innermost says what went wrong
+        while let EvalAltResult::ErrorInFunctionCall(_, _, inner, _) = err {
+            err = *inner;
+        }
+        let line = err.take_position().line();
+        ScriptError::Runtime { hook: hook.name(), line, message: err.to_string() }
+    }
+
+    /// the line of the script the error is on, if it is on one
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ScriptError::Io(_) => None,
+            ScriptError::Parse { line, .. } | ScriptError::Runtime { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Io(err) => write!(f, "could not access script: {}", err),
+            ScriptError::Parse { line, message } => write!(f, "could not parse script{}: {}", on_line(*line), message),
+            ScriptError::Runtime { hook, line, message } => write!(f, "script failed in {}{}: {}", hook, on_line(*line), message),
+        }
+    }
+}
+
+fn on_line(line: Option<usize>) -> String {
+    line.map(|line| format!(" on line {}", line)).unwrap_or_default()
+}
+
+impl Error for ScriptError {}
+
+impl From<io::Error> for ScriptError {
+    fn from(err: io::Error) -> Self {
+        ScriptError::Io(err)
+    }
+}
+
+impl From<ParseError> for ScriptError {
+    fn from(err: ParseError) -> Self {
+        ScriptError::Parse { line: err.position().line(), message: err.err_type().to_string() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+fn on_wave(game) {
+    clear();
+    place(2, 2);
+    place(6, 2, "invader_large");
+    pattern("zigzag");
+}
+
+fn on_invader_killed(game, x, y) {
+    march_ms(100 * game.invaders);
+}
+"#;
+
+    #[test]
+    fn test_hooks_queue_commands() {
+        let mut script: Script = SCRIPT.parse().unwrap();
+        let commands = script.run(Hook::Wave, State::default());
+        assert_eq!(
+            commands,
+            [
+                Command::Clear,
+                Command::Place(Pos::new(2, 2), sprite::INVADER),
+                Command::Place(Pos::new(6, 2), sprite::INVADER_LARGE),
+                Command::Pattern(PatternKind::ZigZag),
+            ]
+        );
+        let state = State { invaders: 3, ..State::default() };
+        assert_eq!(script.run(Hook::InvaderKilled(Pos::new(2, 2)), state), [Command::MarchMs(300)]);
+        // not defined, nothing happens
+        assert!(script.run(Hook::ArmyMoved, state).is_empty());
+        assert!(script.error().is_none());
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let err = "fn on_wave(game) {\n    place(1, 2\n}".parse::<Script>().err().unwrap();
+        assert!(matches!(err, ScriptError::Parse { .. }), "{}", err);
+        assert_eq!(err.line(), Some(3));
+
+        let mut script: Script = "fn on_wave(game) {\n    clear();\n    pattern(\"spiral\");\n}".parse().unwrap();
+        assert!(script.run(Hook::Wave, State::default()).is_empty());
+        let err = script.error().unwrap();
+        assert_eq!(err.line(), Some(3));
+        assert!(err.to_string().starts_with("script failed in on_wave on line 3: "), "{}", err);
+        assert!(err.to_string().contains("spiral"), "{}", err);
+        // a broken script stays stopped, saved and loaded or copied too
+        assert!(script.run(Hook::Wave, State::default()).is_empty());
+        let loaded: Script = toml::from_str(&toml::to_string(&script).unwrap()).unwrap();
+        for mut copy in [loaded, script.clone()] {
+            assert_eq!(copy.error().map(ToString::to_string), script.error().map(ToString::to_string));
+            assert!(copy.run(Hook::Wave, State::default()).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut script: Script = "fn on_wave(game) { loop { } }".parse().unwrap();
+        script.run(Hook::Wave, State::default());
+        assert!(script.error().is_some());
+        // no clock and no files
+        for source in ["fn on_wave(game) { timestamp(); }", "fn on_wave(game) { import \"other\" as other; }"] {
+            let mut script: Script = source.parse().unwrap();
+            script.run(Hook::Wave, State::default());
+            assert!(script.error().is_some(), "{}", source);
+        }
+    }
+}
@@ -1,43 +1,97 @@
 use std::time::Duration;
 use rusty_time::timer::Timer;
+use serde::{Deserialize, Serialize};
 
-use crate::frame::{Drawable, Frame};
+use crate::frame::{Drawable, Frame, Pos, Rect};
+use crate::sprite::{self, Collider};
 
+#[derive(Serialize, Deserialize)]
 pub struct Shot {
-    pub x: usize,
-    pub y: usize,
+    pub pos: Pos,
     pub exploding: bool,
+    // every cell the shot passed through during the last update
+    path: Rect,
+    // time it takes to move up one row
+    step: Duration,
+    // time since the last step, carried over so a long frame moves several rows
+    elapsed: Duration,
+    // how long the explosion stays, armed by `explode`
+    #[serde(with = "crate::save::timer")]
     timer: Timer,
 }
 
 impl Shot {
-    pub fn new(x: usize, y: usize) -> Self {
-        Self { x, y, exploding: false, timer: Timer::from_millis(50) }
+    pub fn new(pos: Pos, step: Duration) -> Self {
+        Self {
+            pos,
+            exploding: false,
+            path: Rect::new(pos, 1, 1),
+            step,
+            elapsed: Duration::default(),
+            timer: Timer::from_millis(0),
+        }
     }
 
-    pub fn update(&mut self, delta: Duration) {
-        self.timer.update(delta);
-        if self.timer.ready && !self.exploding {
-            if self.y > 0 {
-                self.y -= 1 // move upwards
+    /// returns the cells the shot left, in the order it left them
+    pub fn update(&mut self, delta: Duration) -> Vec<Pos> {
+        let mut left = Vec::new();
+        if self.exploding {
+            self.timer.update(delta);
+            self.path = Rect::new(self.pos, 1, 1);
+            return left;
+        }
+        let start = self.pos;
+        self.elapsed += delta;
+        while self.elapsed >= self.step {
+            self.elapsed -= self.step;
+            match self.pos.offset(0, -1) {
+                Some(pos) => {
+                    left.push(self.pos);
+                    self.pos = pos // move upwards
+                }
+                None => break,
             }
-            self.timer.reset();
         }
+        self.path = Rect::new(self.pos, 1, start.y - self.pos.y + 1);
+        left
     }
 
-    pub fn explode(&mut self) {
+    /// the column segment from where the shot started this update to where it is now
+    pub fn path(&self) -> Rect {
+        self.path
+    }
+
+    /// explodes at `pos`, which should be on the shot's path
+    pub fn explode(&mut self, pos: Pos) {
+        self.pos = pos;
         self.exploding = true;
         self.timer = Timer::from_millis(250);
     }
 
+    /// time until the shot next moves up, or until its explosion is over
+    pub fn time_left(&self) -> Duration {
+        if self.exploding {
+            self.timer.time_left
+        } else {
+            self.step.saturating_sub(self.elapsed)
+        }
+    }
+
     pub fn dead(&self) -> bool {
         // condition will evaluate to bool
-        (self.exploding && self.timer.ready) || (self.y == 0)
+        (self.exploding && self.timer.ready) || (self.pos.y == 0)
+    }
+}
+
+impl Collider for Shot {
+    fn hitbox(&self) -> Rect {
+        sprite::SHOT.hitbox(self.pos)
     }
 }
 
 impl Drawable for Shot {
     fn draw(&self, frame: &mut Frame) {
-        frame[self.x][self.y] = if self.exploding { "⦻" } else { "⨨" };
+        let sprite = if self.exploding { sprite::EXPLOSION } else { sprite::SHOT };
+        sprite.draw(frame, self.pos, 0);
     }
 }
\ No newline at end of file
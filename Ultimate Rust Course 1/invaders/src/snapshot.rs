@@ -0,0 +1,209 @@
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::Command;
+
+use crate::frame::Frame;
+use crate::render::Theme;
+
+/// set to anything to have `assert_golden` write the golden file instead of comparing
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// what a frame can be dumped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// the glyphs alone
+    Text,
+    /// the glyphs in the theme's colours, for `cat` in a terminal
+    Ansi,
+    /// a standalone page in the theme's colours
+    Html,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Text, Format::Ansi, Format::Html];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Ansi => "ansi",
+            Format::Html => "html",
+        }
+    }
+
+    /// file extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Ansi => "ans",
+            Format::Html => "html",
+        }
+    }
+
+    /// the format of a file, going by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL.iter().copied().find(|format| format.extension() == extension)
+    }
+
+    pub fn export(self, frame: &Frame, theme: Theme) -> String {
+        match self {
+            Format::Text => to_text(frame),
+            Format::Ansi => to_ansi(frame, theme),
+            Format::Html => to_html(frame, theme),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == s)
+            .ok_or_else(|| format!("unknown format `{}`, expected text, ansi or html", s))
+    }
+}
+
+// one line per row, trailing blanks dropped so golden files survive editors
+fn lines(frame: &Frame) -> impl Iterator<Item = String> + '_ {
+    (0..frame.height()).map(move |y| frame.row(y).collect::<String>().trim_end().to_string())
+}
+
+/// the frame as plain text, one line per row
+pub fn to_text(frame: &Frame) -> String {
+    lines(frame).map(|line| line + "\n").collect()
+}
+
+/// the frame as text with ANSI colour codes, each row resets the colours at its end
+pub fn to_ansi(frame: &Frame, theme: Theme) -> String {
+    let (_, background, foreground) = theme.colors();
+    let mut out = String::new();
+    for y in 0..frame.height() {
+        let row: String = frame.row(y).collect();
+        let _ = writeln!(
+            out,
+            "{}{}{}{}",
+            SetBackgroundColor(background).ansi_code(),
+            SetForegroundColor(foreground).ansi_code(),
+            row,
+            ResetColor.ansi_code()
+        );
+    }
+    out
+}
+
+// CSS for the colours themes use, `None` leaves the browser's own
+fn css(color: Color) -> Option<&'static str> {
+    match color {
+        Color::Black => Some("black"),
+        Color::White => Some("white"),
+        Color::Blue => Some("blue"),
+        Color::Green => Some("lime"),
+        Color::Magenta => Some("magenta"),
+        Color::Yellow => Some("yellow"),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// the frame as a standalone HTML page
+pub fn to_html(frame: &Frame, theme: Theme) -> String {
+    let (border, background, foreground) = theme.colors();
+    let mut body = String::from("body { margin: 0; padding: 1em;");
+    if let Some(border) = css(border) {
+        let _ = write!(body, " background: {};", border);
+    }
+    body.push_str(" }");
+    let mut pre = String::from("pre { display: inline-block; margin: 0; font-family: monospace; line-height: 1.2;");
+    if let Some(background) = css(background) {
+        let _ = write!(pre, " background: {};", background);
+    }
+    if let Some(foreground) = css(foreground) {
+        let _ = write!(pre, " color: {};", foreground);
+    }
+    pre.push_str(" }");
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>invaders</title>\n");
+    let _ = writeln!(out, "<style>\n{}\n{}\n</style>", body, pre);
+    out.push_str("</head>\n<body>\n<pre>");
+    for y in 0..frame.height() {
+        let row: String = frame.row(y).collect();
+        let _ = writeln!(out, "{}", escape(&row));
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+/// writes the frame next to `stem` in every format, returns the files written
+pub fn save(frame: &Frame, theme: Theme, stem: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    Format::ALL
+        .iter()
+        .map(|format| {
+            let path = stem.as_ref().with_extension(format.extension());
+            fs::write(&path, format.export(frame, theme))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// compares the frame against a checked-in golden file, in the format its extension
+/// names, and panics with both versions if they differ
+///
+/// with `UPDATE_SNAPSHOTS` set the golden file is written instead.
+pub fn assert_golden(frame: &Frame, theme: Theme, golden: impl AsRef<Path>) {
+    let golden = golden.as_ref();
+    let format = Format::from_path(golden)
+        .unwrap_or_else(|| panic!("{}: no snapshot format uses this extension", golden.display()));
+    let actual = format.export(frame, theme);
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        fs::write(golden, &actual).unwrap_or_else(|err| panic!("{}: {}", golden.display(), err));
+        return;
+    }
+    let expected = fs::read_to_string(golden)
+        .unwrap_or_else(|err| panic!("{}: {}, run with {}=1 to create it", golden.display(), err, UPDATE_VAR));
+    if actual != expected {
+        panic!(
+            "{} doesn't match, run with {}=1 if the change is intended\n--- expected\n{}\n--- actual\n{}",
+            golden.display(),
+            UPDATE_VAR,
+            expected,
+            actual
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::Pos;
+
+    #[test]
+    fn test_export() {
+        let mut frame = Frame::new(4, 2);
+        frame.text(Pos::new(0, 0), "<a>");
+        assert_eq!(to_text(&frame), "<a>\n\n");
+        assert!(to_html(&frame, Theme::Classic).contains("<pre>&lt;a&gt; \n    \n</pre>"));
+        let ansi = to_ansi(&frame, Theme::Mono);
+        assert_eq!(ansi.lines().count(), 2);
+        assert!(ansi.lines().all(|line| line.ends_with("\x1b[0m")));
+        assert_eq!("html".parse(), Ok(Format::Html));
+        assert_eq!(Format::from_path(Path::new("start.ans")), Some(Format::Ansi));
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::frame::{Frame, Pos, Rect};
+
+/// a multi-cell picture with one or more animation frames
+///
+/// every frame is a list of rows, every char of a row is one cell.
+/// spaces are transparent, so overlapping sprites don't blank each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprite {
+    /// how the sprite is referred to in save files
+    pub name: &'static str,
+    pub frames: &'static [&'static [&'static str]],
+}
+
+pub const PLAYER: Sprite = Sprite { name: "player", frames: &[&["◢⟁◣"]] };
+pub const PLAYER_LARGE: Sprite = Sprite { name: "player_large", frames: &[&["  ▲  ", "◢███◣"]] };
+pub const INVADER: Sprite = Sprite { name: "invader", frames: &[&["⬢"], &["⬣"]] };
+pub const INVADER_LARGE: Sprite = Sprite {
+    name: "invader_large",
+    frames: &[
+        &["▗▆▖", "▘ ▝"],
+        &["▗▆▖", " ▚▞"],
+    ],
+};
+pub const SHOT: Sprite = Sprite { name: "shot", frames: &[&["⨨"]] };
+pub const EXPLOSION: Sprite = Sprite { name: "explosion", frames: &[&["⦻"]] };
+pub const BUNKER: Sprite = Sprite { name: "bunker", frames: &[&["▓"]] };
+
+pub const ALL: [Sprite; 7] = [PLAYER, PLAYER_LARGE, INVADER, INVADER_LARGE, SHOT, EXPLOSION, BUNKER];
+
+/// the built-in sprite called `name`
+pub fn by_name(name: &str) -> Option<Sprite> {
+    ALL.iter().copied().find(|sprite| sprite.name == name)
+}
+
+impl Sprite {
+    pub fn width(&self) -> usize {
+        self.frames
+            .iter()
+            .flat_map(|rows| rows.iter())
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.frames.iter().map(|rows| rows.len()).max().unwrap_or(0)
+    }
+
+    /// the cells a sprite at `pos` occupies
+    pub fn hitbox(&self, pos: Pos) -> Rect {
+        Rect::new(pos, self.width(), self.height())
+    }
+
+    /// the cells of animation frame `anim` relative to the top-left corner,
+    /// `anim` wraps around so callers can just count up
+    pub fn cells(&self, anim: usize) -> impl Iterator<Item = (usize, usize, &'static str)> {
+        let rows: &'static [&'static str] = if self.frames.is_empty() {
+            &[]
+        } else {
+            self.frames[anim % self.frames.len()]
+        };
+        rows.iter().enumerate().flat_map(|(dy, row)| {
+            row.char_indices()
+                .enumerate()
+                .map(move |(dx, (idx, c))| (dx, dy, &row[idx..idx + c.len_utf8()]))
+        })
+    }
+
+    /// draws frame `anim` with its top-left corner at `pos`, clipped to the frame
+    pub fn draw(&self, frame: &mut Frame, pos: Pos, anim: usize) {
+        for (dx, dy, glyph) in self.cells(anim) {
+            if glyph != " " {
+                frame.set(Pos::new(pos.x + dx, pos.y + dy), glyph);
+            }
+        }
+    }
+}
+
+// sprites are saved by name, their pictures live in the binary
+impl Serialize for Sprite {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sprite {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        by_name(&name).ok_or_else(|| de::Error::custom(format!("unknown sprite `{}`", name)))
+    }
+}
+
+/// anything that can be hit
+pub trait Collider {
+    fn hitbox(&self) -> Rect;
+
+    fn collides_with(&self, other: &dyn Collider) -> bool {
+        self.hitbox().intersects(&other.hitbox())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        assert_eq!((INVADER.width(), INVADER.height()), (1, 1));
+        assert_eq!((INVADER_LARGE.width(), INVADER_LARGE.height()), (3, 2));
+        assert_eq!((PLAYER.width(), PLAYER.height()), (3, 1));
+    }
+
+    #[test]
+    fn test_draw_is_transparent_and_clipped() {
+        let mut frame = Frame::new(4, 2);
+        frame.set(Pos::new(2, 1), "x");
+        INVADER_LARGE.draw(&mut frame, Pos::new(1, 0), 0);
+        assert_eq!(frame.row(0).collect::<String>(), " ▗▆▖");
+        assert_eq!(frame.row(1).collect::<String>(), " ▘x▝");
+        // only the left column fits
+        INVADER_LARGE.draw(&mut frame, Pos::new(3, 1), 1);
+        assert_eq!(frame.get(Pos::new(3, 1)), Some("▗"));
+    }
+
+    #[test]
+    fn test_hitbox() {
+        let hitbox = INVADER_LARGE.hitbox(Pos::new(5, 5));
+        assert!(hitbox.contains(Pos::new(7, 6)));
+        assert!(!hitbox.contains(Pos::new(8, 6)));
+        assert!(hitbox.intersects(&SHOT.hitbox(Pos::new(6, 5))));
+        assert!(!hitbox.intersects(&SHOT.hitbox(Pos::new(6, 4))));
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use rusty_time::timer::Timer;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{GameEvent, Subscriber};
+use crate::frame::{Drawable, Frame};
+
+/// file the lifetime statistics are kept in, in the working directory
+pub const STATS_FILE: &str = "invaders-stats.toml";
+
+/// how many games the high score table keeps
+const MAX_SCORES: usize = 10;
+
+/// how long an achievement, or any other notice, stays on screen
+pub const NOTICE_TIME: Duration = Duration::from_millis(2500);
+
+/// counters for one game, or summed over all of them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games_played: u64,
+    pub games_won: u64,
+    pub shots_fired: u64,
+    pub invaders_killed: u64,
+    pub waves_cleared: u64,
+    /// quickest time from a wave spawning to its last invader going down
+    pub fastest_wave_ms: Option<u64>,
+}
+
+impl Stats {
+    /// share of shots that hit, 0 before the first shot
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.invaders_killed as f32 / self.shots_fired as f32
+        }
+    }
+
+    /// adds another set of counters, keeping the faster of the two waves
+    pub fn add(&mut self, other: &Stats) {
+        self.games_played += other.games_played;
+        self.games_won += other.games_won;
+        self.shots_fired += other.shots_fired;
+        self.invaders_killed += other.invaders_killed;
+        self.waves_cleared += other.waves_cleared;
+        self.fastest_wave_ms = match (self.fastest_wave_ms, other.fastest_wave_ms) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "games played    {}", self.games_played)?;
+        writeln!(f, "games won       {}", self.games_won)?;
+        writeln!(f, "shots fired     {}", self.shots_fired)?;
+        writeln!(f, "accuracy        {:.0}%", self.accuracy() * 100.0)?;
+        writeln!(f, "invaders killed {}", self.invaders_killed)?;
+        writeln!(f, "waves cleared   {}", self.waves_cleared)?;
+        match self.fastest_wave_ms {
+            Some(ms) => write!(f, "fastest wave    {:.1}s", ms as f32 / 1000.0),
+            None => write!(f, "fastest wave    -"),
+        }
+    }
+}
+
+/// unlocked once and kept for good
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    FirstBlood,
+    WaveBreaker,
+    Sharpshooter,
+    Speedrunner,
+    Centurion,
+    Defender,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstBlood,
+        Achievement::WaveBreaker,
+        Achievement::Sharpshooter,
+        Achievement::Speedrunner,
+        Achievement::Centurion,
+        Achievement::Defender,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "First Blood",
+            Achievement::WaveBreaker => "Wave Breaker",
+            Achievement::Sharpshooter => "Sharpshooter",
+            Achievement::Speedrunner => "Speedrunner",
+            Achievement::Centurion => "Centurion",
+            Achievement::Defender => "Defender",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "shoot down an invader",
+            Achievement::WaveBreaker => "clear a wave",
+            Achievement::Sharpshooter => "hit 90% of 30 or more shots in one game",
+            Achievement::Speedrunner => "clear a wave in under 20 seconds",
+            Achievement::Centurion => "shoot down 100 invaders over all games",
+            Achievement::Defender => "win a game",
+        }
+    }
+
+    /// whether the achievement is earned, given the current game and all games including it
+    pub fn earned(self, session: &Stats, lifetime: &Stats) -> bool {
+        match self {
+            Achievement::FirstBlood => lifetime.invaders_killed > 0,
+            Achievement::WaveBreaker => lifetime.waves_cleared > 0,
+            Achievement::Sharpshooter => session.shots_fired >= 30 && session.accuracy() >= 0.9,
+            Achievement::Speedrunner => lifetime.fastest_wave_ms.is_some_and(|ms| ms < 20_000),
+            Achievement::Centurion => lifetime.invaders_killed >= 100,
+            Achievement::Defender => lifetime.games_won > 0,
+        }
+    }
+}
+
+impl fmt::Display for Achievement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.title())
+    }
+}
+
+/// one finished game in the high score table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    pub score: u32,
+    /// the wave the game ended in, counting from 1
+    pub wave: usize,
+    pub won: bool,
+}
+
+/// the best games, highest score first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HighScores(pub Vec<Score>);
+
+impl HighScores {
+    /// enters a game, returns its place counting from 1 if it made the table
+    pub fn add(&mut self, score: Score) -> Option<usize> {
+        // a later game with the same score ranks below the earlier one
+        let idx = self.0.iter().position(|entry| entry.score < score.score).unwrap_or(self.0.len());
+        if idx >= MAX_SCORES {
+            return None;
+        }
+        self.0.insert(idx, score);
+        self.0.truncate(MAX_SCORES);
+        Some(idx + 1)
+    }
+}
+
+impl fmt::Display for HighScores {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "no games finished yet");
+        }
+        write!(f, "  #  score  wave")?;
+        for (idx, entry) in self.0.iter().enumerate() {
+            let result = if entry.won { "won" } else { "" };
+            write!(f, "\n{:>3}  {:>5}  {:>4}  {}", idx + 1, entry.score, entry.wave, result)?;
+        }
+        Ok(())
+    }
+}
+
+/// what is kept between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Record {
+    pub lifetime: Stats,
+    pub last_game: Stats,
+    pub achievements: Vec<Achievement>,
+    pub scores: HighScores,
+}
+
+impl Record {
+    /// like a config, a missing file just means a fresh record
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, StatsError> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| StatsError::Parse(err.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StatsError> {
+        let text = toml::to_string(self).map_err(|err| StatsError::Parse(err.to_string()))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// unlocks whatever `session` earned on top of the lifetime counters,
+    /// returns the achievements that are new
+    pub fn unlock(&mut self, session: &Stats) -> Vec<Achievement> {
+        let mut lifetime = self.lifetime.clone();
+        lifetime.add(session);
+        let new: Vec<Achievement> = Achievement::ALL
+            .iter()
+            .copied()
+            .filter(|achievement| !self.achievements.contains(achievement))
+            .filter(|achievement| achievement.earned(session, &lifetime))
+            .collect();
+        self.achievements.extend(&new);
+        new
+    }
+
+    /// folds a finished or abandoned game into the lifetime counters
+    pub fn finish(&mut self, session: &Stats) {
+        self.unlock(session);
+        self.lifetime.add(session);
+        self.last_game = session.clone();
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "last game\n{}\n", self.last_game)?;
+        writeln!(f, "all games\n{}\n", self.lifetime)?;
+        write!(f, "achievements ({}/{})", self.achievements.len(), Achievement::ALL.len())?;
+        for achievement in Achievement::ALL {
+            let mark = if self.achievements.contains(&achievement) { "x" } else { " " };
+            write!(f, "\n[{}] {:<13} {}", mark, achievement, achievement.description())?;
+        }
+        Ok(())
+    }
+}
+
+/// counts what happens in one game, subscribe it to the game's events
+#[derive(Debug, Default)]
+pub struct Session {
+    pub stats: Stats,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Subscriber for Session {
+    fn notify(&mut self, event: &GameEvent) {
+        let stats = &mut self.stats;
+        match *event {
+            GameEvent::ShotFired { .. } => stats.shots_fired += 1,
+            GameEvent::InvaderKilled { .. } => stats.invaders_killed += 1,
+            GameEvent::WaveCleared { time, .. } => {
+                stats.waves_cleared += 1;
+                let ms = time.as_millis() as u64;
+                stats.fastest_wave_ms = Some(stats.fastest_wave_ms.map_or(ms, |fastest| fastest.min(ms)));
+            }
+            GameEvent::GameOver { won } => {
+                stats.games_played += 1;
+                if won {
+                    stats.games_won += 1;
+                }
+            }
+            GameEvent::ShotMoved { .. }
+            | GameEvent::ArmyMoved
+            | GameEvent::InvaderAbove
+            | GameEvent::ScriptFailed
+            | GameEvent::BunkerHit { .. }
+            | GameEvent::PlayerHit { .. } => {}
+        }
+    }
+}
+
+/// achievement banners, shown one after another at the top of the screen
+#[derive(Default)]
+pub struct Notifications {
+    queue: VecDeque<Achievement>,
+    timer: Option<Timer>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, achievement: Achievement) {
+        self.queue.push_back(achievement);
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        let timer = match &mut self.timer {
+            Some(timer) => timer,
+            None if self.queue.is_empty() => return,
+            None => self.timer.insert(Timer::from_millis(NOTICE_TIME.as_millis() as u64)),
+        };
+        timer.update(delta);
+        if timer.ready {
+            self.queue.pop_front();
+            self.timer = None;
+        }
+    }
+}
+
+impl Drawable for Notifications {
+    fn draw(&self, frame: &mut Frame) {
+        if let Some(achievement) = self.queue.front() {
+            frame.text_centered(2, &format!(" achievement unlocked: {} ", achievement));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StatsError {
+    Io(io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Io(err) => write!(f, "could not access stats: {}", err),
+            StatsError::Parse(msg) => write!(f, "could not read stats: {}", msg),
+        }
+    }
+}
+
+impl Error for StatsError {}
+
+impl From<io::Error> for StatsError {
+    fn from(err: io::Error) -> Self {
+        StatsError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::Pos;
+
+    #[test]
+    fn test_session_counts_kills_and_waves() {
+        let mut session = Session::new();
+        let pos = Pos::new(3, 3);
+        for event in [
+            GameEvent::ShotFired { pos },
+            GameEvent::ShotFired { pos },
+            GameEvent::InvaderKilled { pos },
+            GameEvent::WaveCleared { wave: 0, time: Duration::from_millis(1500) },
+        ] {
+            session.notify(&event);
+        }
+        assert_eq!(session.stats.invaders_killed, 1);
+        assert_eq!(session.stats.waves_cleared, 1);
+        assert_eq!(session.stats.fastest_wave_ms, Some(1500));
+        assert_eq!(session.stats.accuracy(), 0.5);
+    }
+
+    #[test]
+    fn test_achievements_unlock_once() {
+        let mut record = Record::default();
+        let session = Stats { shots_fired: 1, invaders_killed: 1, ..Stats::default() };
+        assert_eq!(record.unlock(&session), vec![Achievement::FirstBlood]);
+        assert!(record.unlock(&session).is_empty());
+        record.finish(&session);
+        assert_eq!(record.lifetime.invaders_killed, 1);
+        assert_eq!(record.achievements, vec![Achievement::FirstBlood]);
+    }
+
+    #[test]
+    fn test_high_scores_keep_the_best() {
+        let mut scores = HighScores::default();
+        for score in 0..12 {
+            scores.add(Score { score: score * 10, wave: 1, won: false });
+        }
+        assert_eq!(scores.0.len(), MAX_SCORES);
+        assert_eq!(scores.0[0].score, 110);
+        assert_eq!(scores.add(Score { score: 0, wave: 1, won: false }), None);
+        assert_eq!(scores.add(Score { score: 55, wave: 2, won: false }), Some(7));
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let mut record = Record::default();
+        record.finish(&Stats { games_played: 1, fastest_wave_ms: Some(9000), ..Stats::default() });
+        record.scores.add(Score { score: 120, wave: 2, won: false });
+        let text = toml::to_string(&record).unwrap();
+        assert_eq!(toml::from_str::<Record>(&text).unwrap(), record);
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::music::Note;
+use crate::rng::Rng;
+
+/// samples per second of everything generated here, mono
+pub const SAMPLE_RATE: u32 = 22_050;
+
+/// the raw sound of a tone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wave {
+    /// a pulse that is high for `duty` of every period, 0.5 is a plain square
+    Square { duty: f32 },
+    /// random levels, held for one period each so the pitch still colours it
+    Noise,
+}
+
+/// how loud a tone is over its length: a ramp up, full volume, a ramp down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: Duration,
+    pub release: Duration,
+}
+
+impl Envelope {
+    /// short ramps, enough to keep the tone from clicking
+    pub const PLUCK: Envelope = Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(10) };
+
+    /// the volume factor at `time` into a tone of `length`
+    fn level(&self, time: f32, length: f32) -> f32 {
+        let attack = self.attack.as_secs_f32();
+        let release = self.release.as_secs_f32();
+        let up = if attack > 0.0 { time / attack } else { 1.0 };
+        let down = if release > 0.0 { (length - time) / release } else { 1.0 };
+        up.min(down).clamp(0.0, 1.0)
+    }
+}
+
+/// one tone, its pitch sliding from `from_hz` to `to_hz`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    pub wave: Wave,
+    pub from_hz: f32,
+    pub to_hz: f32,
+    pub length: Duration,
+    /// peak amplitude, 1.0 is full scale
+    pub volume: f32,
+    pub envelope: Envelope,
+}
+
+impl Tone {
+    /// a plain square note at one pitch
+    pub const fn note(hz: f32, millis: u64, volume: f32) -> Self {
+        Self {
+            wave: Wave::Square { duty: 0.5 },
+            from_hz: hz,
+            to_hz: hz,
+            length: Duration::from_millis(millis),
+            volume,
+            envelope: Envelope::PLUCK,
+        }
+    }
+
+    pub fn sample_count(&self) -> usize {
+        (self.length.as_secs_f64() * SAMPLE_RATE as f64).round() as usize
+    }
+
+    /// appends the tone to `out`, `rng` is only drawn from for noise
+    pub fn render(&self, rng: &mut Rng, out: &mut Vec<f32>) {
+        let count = self.sample_count();
+        let length = count as f32 / SAMPLE_RATE as f32;
+        // position within the current period, 0 to 1
+        let mut phase = 0.0f32;
+        let mut level = 1.0f32;
+        for idx in 0..count {
+            let time = idx as f32 / SAMPLE_RATE as f32;
+            let progress = if count > 1 { idx as f32 / (count - 1) as f32 } else { 0.0 };
+            let hz = self.from_hz + (self.to_hz - self.from_hz) * progress;
+            let raw = match self.wave {
+                Wave::Square { duty } => {
+                    if phase < duty { 1.0 } else { -1.0 }
+                }
+                Wave::Noise => level,
+            };
+            out.push(raw * self.volume * self.envelope.level(time, length));
+            phase += hz / SAMPLE_RATE as f32;
+            if phase >= 1.0 {
+                phase -= phase.floor();
+                if self.wave == Wave::Noise {
+                    level = rng.next_u64() as f32 / u64::MAX as f32 * 2.0 - 1.0;
+                }
+            }
+        }
+    }
+}
+
+/// the tones one after the other
+pub fn sequence(tones: &[Tone], rng: &mut Rng) -> Vec<f32> {
+    let mut out = Vec::with_capacity(tones.iter().map(Tone::sample_count).sum());
+    for tone in tones {
+        tone.render(rng, &mut out);
+    }
+    out
+}
+
+// note pitches, in hz
+const C4: f32 = 261.63;
+const E4: f32 = 329.63;
+const G4: f32 = 392.00;
+const C5: f32 = 523.25;
+const E5: f32 = 659.25;
+const G5: f32 = 783.99;
+const C6: f32 = 1046.50;
+
+/// the game's sound effects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Effect {
+    Pew,
+    Explode,
+    Win,
+    Lose,
+    Startup,
+    /// an invader is in line above the ship, for players who can't see it
+    Cue,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 6] = [Effect::Pew, Effect::Explode, Effect::Win, Effect::Lose, Effect::Startup, Effect::Cue];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Pew => "pew",
+            Effect::Explode => "explode",
+            Effect::Win => "win",
+            Effect::Lose => "lose",
+            Effect::Startup => "startup",
+            Effect::Cue => "cue",
+        }
+    }
+
+    pub fn tones(self) -> Vec<Tone> {
+        match self {
+            Effect::Pew => vec![Tone {
+                wave: Wave::Square { duty: 0.25 },
+                from_hz: 1400.0,
+                to_hz: 300.0,
+                length: Duration::from_millis(140),
+                volume: 0.25,
+                envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(110) },
+            }],
+            Effect::Explode => vec![Tone {
+                wave: Wave::Noise,
+                from_hz: 6000.0,
+                to_hz: 600.0,
+                length: Duration::from_millis(380),
+                volume: 0.4,
+                envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(360) },
+            }],
+            Effect::Win => vec![
+                Tone::note(C5, 110, 0.25),
+                Tone::note(E5, 110, 0.25),
+                Tone::note(G5, 110, 0.25),
+                Tone { envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(300) }, ..Tone::note(C6, 450, 0.25) },
+            ],
+            Effect::Lose => vec![
+                Tone::note(G4, 180, 0.3),
+                Tone::note(E4, 180, 0.3),
+                Tone {
+                    wave: Wave::Square { duty: 0.5 },
+                    from_hz: C4,
+                    to_hz: C4 / 2.0,
+                    length: Duration::from_millis(600),
+                    volume: 0.3,
+                    envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(450) },
+                },
+            ],
+            Effect::Startup => vec![
+                Tone::note(C4, 70, 0.2),
+                Tone::note(E4, 70, 0.2),
+                Tone::note(G4, 70, 0.2),
+                Tone::note(C5, 70, 0.2),
+                Tone { envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(150) }, ..Tone::note(E5, 200, 0.2) },
+            ],
+            // two high blips with a narrow pulse, unlike anything else in the game
+            Effect::Cue => vec![
+                Tone { wave: Wave::Square { duty: 0.125 }, ..Tone::note(C6 * 2.0, 35, 0.2) },
+                Tone::note(0.0, 25, 0.0),
+                Tone { wave: Wave::Square { duty: 0.125 }, ..Tone::note(C6 * 2.0, 35, 0.2) },
+            ],
+        }
+    }
+
+    /// the effect as samples at `SAMPLE_RATE`, the same every time
+    pub fn samples(self) -> Vec<f32> {
+        sequence(&self.tones(), &mut Rng::new(self as u64))
+    }
+}
+
+/// one step of the army's march, short enough for the fastest tempo
+pub fn march(note: Note) -> Vec<f32> {
+    let tone = Tone {
+        wave: Wave::Square { duty: 0.5 },
+        from_hz: note.hz(),
+        to_hz: note.hz(),
+        length: Duration::from_millis(90),
+        volume: 0.35,
+        envelope: Envelope { attack: Duration::from_millis(2), release: Duration::from_millis(60) },
+    };
+    sequence(&[tone], &mut Rng::new(0))
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_tone_length_and_peak() {
+        let tone = Tone::note(440.0, 100, 0.5);
+        let samples = sequence(&[tone], &mut Rng::new(0));
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 10);
+        assert!((peak(&samples) - 0.5).abs() < 1e-6);
+        // the envelope starts and ends silent, so notes don't click
+        assert_eq!(samples[0], 0.0);
+        assert!(samples.last().unwrap().abs() < 0.01);
+    }
+
+    #[test]
+    fn test_effects() {
+        for effect in Effect::ALL {
+            let samples = effect.samples();
+            let count: usize = effect.tones().iter().map(Tone::sample_count).sum();
+            assert_eq!(samples.len(), count, "{}", effect);
+            let peak = peak(&samples);
+            assert!(peak > 0.1 && peak <= 1.0, "{} peaks at {}", effect, peak);
+            assert_eq!(samples, effect.samples(), "{}", effect);
+        }
+    }
+}
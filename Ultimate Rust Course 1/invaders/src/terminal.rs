@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::io;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once};
+
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::ExecutableCommand;
+
+/// puts the terminal into game mode and restores it when dropped
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        install_panic_hook();
+        // build the guard first, so a failure half-way through still restores
+        let guard = Self { _private: () };
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        stdout.execute(EnterAlternateScreen)?;
+        stdout.execute(Hide)?;
+        // terminals without mouse reporting ignore this, the keyboard works either way
+        let _ = stdout.execute(EnableMouseCapture);
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// best-effort restore of the terminal, safe to call more than once
+pub fn restore() {
+    let mut stdout = io::stdout();
+    let _ = stdout.execute(DisableMouseCapture);
+    let _ = stdout.execute(Show);
+    let _ = stdout.execute(LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+/// restores the terminal before the default hook prints the panic message.
+/// installed by the first guard only, later ones would chain another copy.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            default_hook(info);
+        }));
+    });
+}
+
+/// flag that is raised once SIGINT or SIGTERM is received
+pub fn quit_signal() -> Result<Arc<AtomicBool>, Box<dyn Error>> {
+    let quit = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&quit);
+    ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))?;
+    Ok(quit)
+}
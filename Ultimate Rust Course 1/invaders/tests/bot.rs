@@ -0,0 +1,45 @@
+// Balance checks: the bot has to keep beating the easier presets.
+
+use std::time::Duration;
+
+use invaders::bot::{self, Bot};
+use invaders::config::Difficulty;
+use invaders::frame::Pos;
+use invaders::game::{Action, Game};
+use invaders::invaders::{Invader, Invaders};
+use invaders::sprite::{self, Collider};
+
+const LIMIT: Duration = Duration::from_secs(600);
+
+#[test]
+fn test_bot_wins_easy_and_normal() {
+    for difficulty in [Difficulty::Easy, Difficulty::Normal] {
+        let report = bot::run(&difficulty.config(), 0, 1, LIMIT);
+        assert_eq!(report.wins, 1, "bot lost on {}: {}", difficulty, report);
+    }
+}
+
+#[test]
+fn test_run_reports_lost_seeds() {
+    // nobody wins in a second
+    let report = bot::run(&Difficulty::Easy.config(), u64::MAX, 2, Duration::from_secs(1));
+    assert_eq!(report.lost, vec![u64::MAX, 0]);
+    assert!(report.to_string().ends_with("lost seeds: 18446744073709551615 0"));
+}
+
+#[test]
+fn test_bot_goes_for_the_lowest_invader() {
+    let mut game = Game::new();
+    game.invaders = Invaders::from_army(vec![
+        Invader::new(Pos::new(5, 3), sprite::INVADER),
+        Invader::new(Pos::new(30, 8), sprite::INVADER),
+    ]);
+    let bot = Bot::new();
+    assert_eq!(bot.decide(&game), Some(Action::Right));
+    // walk over and fire
+    while bot.decide(&game) == Some(Action::Right) {
+        game.act(Action::Right);
+    }
+    assert_eq!(game.player.hitbox().center().x, 30);
+    assert_eq!(bot.decide(&game), Some(Action::Fire));
+}
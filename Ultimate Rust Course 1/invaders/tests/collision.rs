@@ -0,0 +1,120 @@
+// Regression tests for swept collision, driven through the headless `Game`.
+
+use std::time::Duration;
+
+use invaders::events::GameEvent;
+use invaders::frame::Pos;
+use invaders::config::{Accessibility, Config};
+use invaders::game::{Game, Status};
+use invaders::invaders::{Invader, Invaders};
+use invaders::level::Level;
+use invaders::sprite::{self, Collider};
+use invaders::NUM_ROWS;
+
+fn game_with(invaders: &[Pos]) -> Game {
+    let mut game = Game::new();
+    let army = invaders.iter().map(|&pos| Invader::new(pos, sprite::INVADER)).collect();
+    game.invaders = Invaders::from_army(army);
+    game
+}
+
+// where shots hit an invader
+fn kills(events: &[GameEvent]) -> Vec<Pos> {
+    events
+        .iter()
+        .filter_map(|event| match *event {
+            GameEvent::InvaderKilled { pos } => Some(pos),
+            _ => None,
+        })
+        .collect()
+}
+
+// the column shots travel up in
+fn shot_x(game: &Game) -> usize {
+    game.player.hitbox().center().x
+}
+
+#[test]
+fn test_stalled_frame_does_not_tunnel() {
+    let mut game = Game::new();
+    let x = shot_x(&game);
+    game.invaders = Invaders::from_army(vec![Invader::new(Pos::new(x, 5), sprite::INVADER)]);
+    assert!(game.player.shoot());
+    // one long frame carries the shot past the invader's row
+    let events = game.update(Duration::from_millis(1000));
+    assert_eq!(kills(&events), vec![Pos::new(x, 5)]);
+    assert!(events.iter().any(|event| matches!(event, GameEvent::WaveCleared { wave: 0, .. })));
+}
+
+#[test]
+fn test_invader_stepping_out_of_the_shots_way() {
+    let x = shot_x(&Game::new());
+    let mut game = game_with(&[Pos::new(x, 10)]);
+    game.update(Duration::from_millis(1100));
+    assert!(game.player.shoot());
+    // the shot climbs to row 11, right below the invader
+    let events = game.update(Duration::from_millis(350));
+    assert!(kills(&events).is_empty());
+    // the invader steps right while the shot passes the row it just left
+    let events = game.update(Duration::from_millis(600));
+    assert!(events.contains(&GameEvent::ArmyMoved));
+    assert_eq!(kills(&events), vec![Pos::new(x, 10)]);
+}
+
+#[test]
+fn test_shot_and_invader_swapping_cells() {
+    let x = shot_x(&Game::new());
+    // at the right edge the army steps down instead, towards the shot
+    let mut game = game_with(&[Pos::new(x, 9), Pos::new(39, 0)]);
+    game.update(Duration::from_millis(1100));
+    assert!(game.player.shoot());
+    // shot at row 10, right below the invader
+    game.update(Duration::from_millis(400));
+    // both move: the invader down to row 10, the shot up past row 9
+    let events = game.update(Duration::from_millis(550));
+    assert!(events.contains(&GameEvent::ArmyMoved));
+    assert_eq!(kills(&events).len(), 1);
+    assert_eq!(game.invaders.army().len(), 1);
+}
+
+#[test]
+fn test_bunker_stops_a_shot() {
+    let x = shot_x(&Game::new());
+    let mut game = Game::new();
+    let mut level = Level::default();
+    level.place(Pos::new(x, 5), sprite::INVADER);
+    level.add_bunker(Pos::new(x, 12));
+    game.set_level(level);
+    assert!(game.player.shoot());
+    // the shot would reach the invader in this frame, the bunker is in the way
+    let events = game.update(Duration::from_millis(1000));
+    assert!(events.contains(&GameEvent::BunkerHit { pos: Pos::new(x, 12) }));
+    assert!(kills(&events).is_empty());
+    assert!(game.bunkers.is_empty());
+    assert!(game.player.shoot());
+    let events = game.update(Duration::from_millis(1000));
+    assert_eq!(kills(&events), vec![Pos::new(x, 5)]);
+}
+
+#[test]
+fn test_invader_above_is_announced_once() {
+    let x = shot_x(&Game::new());
+    let mut game = game_with(&[Pos::new(x, 5), Pos::new(x, 3)]);
+    let events = game.update(Duration::from_millis(10));
+    assert_eq!(events.iter().filter(|event| **event == GameEvent::InvaderAbove).count(), 1);
+    // still in line, nothing new to say
+    let events = game.update(Duration::from_millis(10));
+    assert!(!events.contains(&GameEvent::InvaderAbove));
+}
+
+#[test]
+fn test_army_on_the_large_ship_wins() {
+    let config = Config { accessibility: Accessibility { large_glyphs: true, ..Accessibility::default() }, ..Config::default() };
+    let mut game = Game::with_config(config);
+    let top = game.player.hitbox().pos.y;
+    assert_eq!(top, NUM_ROWS - 2);
+    game.invaders = Invaders::from_army(vec![Invader::new(Pos::new(2, top - 1), sprite::INVADER)]);
+    assert_eq!(game.status(), Status::Playing);
+    game.invaders = Invaders::from_army(vec![Invader::new(Pos::new(2, top), sprite::INVADER)]);
+    assert_eq!(game.status(), Status::Lost);
+}
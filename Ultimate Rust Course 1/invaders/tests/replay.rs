@@ -0,0 +1,129 @@
+// Replays: a recorded game plays back to exactly the same end.
+
+use invaders::bot::Bot;
+use invaders::config::Config;
+use invaders::frame::{new_frame, Drawable, Frame, Pos};
+use invaders::game::{Game, Reload, Status, TICK};
+use invaders::invaders::Invaders;
+use invaders::level::Level;
+use invaders::replay::{Playback, Recorder, Replay};
+use invaders::script::Script;
+use invaders::sprite;
+
+fn frame(game: &Game) -> Frame {
+    let mut frame = new_frame();
+    game.draw(&mut frame);
+    frame
+}
+
+// one invader and one bunker block
+fn bunkered() -> Level {
+    let mut level = Level::default();
+    level.place(Pos::new(2, 2), sprite::INVADER);
+    level.add_bunker(Pos::new(10, 15));
+    level
+}
+
+// plays `game` with the bot, editing files at the given ticks, and checks the
+// replay of it ends the same. `name` keeps the replay file apart from other tests'.
+fn assert_replays(name: &str, mut game: Game, mut reloads: Vec<(u64, Reload)>) -> Game {
+    let mut recorder = Recorder::new(&game).unwrap();
+    let mut bot = Bot::new();
+    for tick in 0..6000 {
+        if reloads.first().is_some_and(|(at, _)| *at == tick) {
+            let (_, reload) = reloads.remove(0);
+            recorder.reload(reload.clone());
+            game.reload(reload);
+        }
+        if let Some(action) = bot.update(TICK, &game) {
+            if game.act(action) {
+                recorder.record(action);
+            }
+        }
+        game.update(TICK);
+        recorder.tick();
+        if game.status() != Status::Playing {
+            break;
+        }
+    }
+    assert!(game.score > 0);
+
+    let path = std::env::temp_dir().join(format!("invaders-{}-{}.replay", name, std::process::id()));
+    recorder.finish().save(&path).unwrap();
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut playback = Playback::new(replay);
+    while playback.step().is_some() {}
+    assert!(playback.done());
+    assert_eq!(playback.game.score, game.score);
+    assert_eq!(playback.game.status(), game.status());
+    assert_eq!(frame(&playback.game), frame(&game));
+    game
+}
+
+#[test]
+fn test_playback_matches_the_recorded_game() {
+    // a seed TOML can't store as is
+    assert_replays("plain", Game::with_seed(Config::default(), u64::MAX), Vec::new());
+}
+
+#[test]
+fn test_scripted_game_replays() {
+    let script: Script = r#"
+fn on_wave(game) {
+    clear();
+    for x in range(4, 36, 4) { place(x, 3); }
+    pattern("zigzag");
+}
+
+// the first kills are replaced at the top
+fn on_invader_killed(game, x, y) {
+    if game.score <= 30 { place(x, 1); }
+}
+"#
+    .parse()
+    .unwrap();
+    let mut game = Game::new();
+    game.set_script(script);
+    assert_eq!(game.invaders.army().len(), 8);
+    assert_replays("scripted", game, Vec::new());
+}
+
+#[test]
+fn test_stopped_script_replays() {
+    // fails on the first wave, would change how every later wave moves if it ran again
+    let script: Script = r#"
+fn on_wave(game) {
+    if game.wave == 0 { place(-1, 0); }
+    pattern("zigzag");
+}
+"#
+    .parse()
+    .unwrap();
+    let mut game = Game::new();
+    game.set_script(script);
+    assert!(game.script.as_ref().unwrap().error().is_some());
+    assert_replays("stopped", game, Vec::new());
+}
+
+#[test]
+fn test_reloads_are_replayed() {
+    let config = Config { march_ms: 300, max_shots: 1, ..Config::default() };
+    let script: Script = "fn on_wave(game) { pattern(\"split\"); }".parse().unwrap();
+    let reloads = vec![(100, Reload::Config(config)), (150, Reload::Level(bunkered())), (200, Reload::Script(Box::new(script)))];
+    let game = assert_replays("reloads", Game::new(), reloads);
+    assert_eq!(game.config.march_ms, 300);
+    assert!(game.script.is_some());
+}
+
+#[test]
+fn test_reloaded_bunkers_wait_for_the_next_wave() {
+    let mut game = Game::new();
+    game.reload(Reload::Level(bunkered()));
+    assert!(game.bunkers.is_empty());
+    game.invaders = Invaders::from_army(Vec::new());
+    game.update(TICK);
+    assert_eq!(game.wave, 1);
+    assert_eq!(game.bunkers.cells(), [Pos::new(10, 15)]);
+}
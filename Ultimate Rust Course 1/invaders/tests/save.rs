@@ -0,0 +1,95 @@
+// Saving and loading: a game picked up from a save plays on exactly like the original.
+
+use invaders::bot::Bot;
+use invaders::config::Config;
+use invaders::frame::{new_frame, Drawable, Frame, Pos};
+use invaders::game::{Game, Status, TICK};
+use invaders::level::Level;
+use invaders::save;
+use invaders::sprite;
+
+fn frame(game: &Game) -> Frame {
+    let mut frame = new_frame();
+    game.draw(&mut frame);
+    frame
+}
+
+// a game part-way into `wave`, with shots in flight and the pattern under way
+fn game_in_wave(wave: usize) -> Game {
+    let mut game = Game::new();
+    game.wave = wave;
+    game.invaders = game.spawn_wave(wave);
+    let mut bot = Bot::new();
+    for _ in 0..300 {
+        if let Some(action) = bot.update(TICK, &game) {
+            game.act(action);
+        }
+        game.update(TICK);
+    }
+    game
+}
+
+#[test]
+fn test_loaded_game_plays_on_identically() {
+    for wave in 0..4 {
+        let mut original = game_in_wave(wave);
+        assert_eq!(original.status(), Status::Playing);
+        let text = save::to_string(&original).unwrap();
+        let mut loaded = save::from_str(&text).unwrap();
+        assert_eq!(save::to_string(&loaded).unwrap(), text);
+
+        let (mut bot, mut loaded_bot) = (Bot::new(), Bot::new());
+        for tick in 0..3000 {
+            if let Some(action) = bot.update(TICK, &original) {
+                original.act(action);
+            }
+            if let Some(action) = loaded_bot.update(TICK, &loaded) {
+                loaded.act(action);
+            }
+            let (a, b) = (original.update(TICK), loaded.update(TICK));
+            assert_eq!(a, b, "wave {} tick {}", wave, tick);
+            assert_eq!(frame(&original), frame(&loaded), "wave {} tick {}", wave, tick);
+            assert_eq!(original.status(), loaded.status());
+            if original.status() != Status::Playing {
+                break;
+            }
+        }
+        assert_eq!(original.score, loaded.score);
+    }
+}
+
+#[test]
+fn test_seeds_above_i64_max_survive_saving() {
+    let mut game = Game::with_seed(Config::default(), u64::MAX);
+    game.rng.next_u64();
+    let loaded = save::from_str(&save::to_string(&game).unwrap()).unwrap();
+    assert_eq!(loaded.seed, u64::MAX);
+    assert_eq!(loaded.rng, game.rng);
+}
+
+#[test]
+fn test_save_file_round_trip() {
+    let path = std::env::temp_dir().join(format!("invaders-{}.save", std::process::id()));
+    let game = game_in_wave(1);
+    save::save(&game, &path).unwrap();
+    let loaded = save::load_if_present(&path).unwrap().expect("the save was just written");
+    assert_eq!(frame(&loaded), frame(&game));
+    save::remove(&path).unwrap();
+    assert!(save::load_if_present(&path).unwrap().is_none());
+    // removing twice is fine
+    save::remove(&path).unwrap();
+}
+
+#[test]
+fn test_level_is_saved() {
+    let mut game = Game::new();
+    let mut level = Level { march_ms: Some(700), ..Level::default() };
+    level.place(Pos::new(4, 3), sprite::INVADER_LARGE);
+    level.add_bunker(Pos::new(10, 15));
+    game.set_level(level.clone());
+    let loaded = save::from_str(&save::to_string(&game).unwrap()).unwrap();
+    assert_eq!(loaded.level, Some(level));
+    assert_eq!(loaded.bunkers, game.bunkers);
+    assert_eq!(loaded.invaders.step_interval(), std::time::Duration::from_millis(700));
+    assert_eq!(frame(&loaded), frame(&game));
+}
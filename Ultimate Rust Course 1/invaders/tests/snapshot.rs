@@ -0,0 +1,38 @@
+// Golden snapshots: frames of a seeded game against files in tests/snapshots.
+
+use invaders::bot::Bot;
+use invaders::frame::{new_frame, Drawable, Frame};
+use invaders::game::{Game, TICK};
+use invaders::render::Theme;
+use invaders::snapshot::assert_golden;
+
+fn frame(game: &Game) -> Frame {
+    let mut frame = new_frame();
+    game.draw(&mut frame);
+    frame
+}
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/snapshots/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn test_new_game() {
+    let frame = frame(&Game::new());
+    assert_golden(&frame, Theme::Classic, golden("new_game.txt"));
+    assert_golden(&frame, Theme::Classic, golden("new_game.ans"));
+    assert_golden(&frame, Theme::Neon, golden("new_game.html"));
+}
+
+#[test]
+fn test_mid_game() {
+    let mut game = Game::new();
+    let mut bot = Bot::new();
+    for _ in 0..300 {
+        if let Some(action) = bot.update(TICK, &game) {
+            game.act(action);
+        }
+        game.update(TICK);
+    }
+    assert_golden(&frame(&game), Theme::Classic, golden("mid_game.txt"));
+}